@@ -15,9 +15,9 @@ use vinx::gui::themes::Theme;
 use vinx::gui::ui::UI;
 use vinx::gui::views::List;
 use vinx::gui::win::{Win, WinEvent};
-use vinx::gui::themes::Classic;
+use vinx::gui::themes::{Classic, ClassicFactory};
 use vinx::gui::traits::View;
-use vinx::gui::views::{Button, Edit, CheckBox};
+use vinx::gui::views::Button;
 
 const WIDTH: u32 = 1920;
 const HEIGHT: u32 = 1080;
@@ -50,15 +50,27 @@ fn main() {
     set_provider(Box::new(assets));
 
     let layout = include_str!("layout.xml");
-    let mut ui = UI::from_xml(layout, WIDTH, HEIGHT, Classic::typeface()).unwrap();
-
-    if let Some(button) = ui.get_view("btn1") {
-        button.borrow_mut().on_event(EventType::Click, Box::new(button1_click));
+    let mut ui = UI::new(WIDTH, HEIGHT, Classic::typeface());
+    // Handlers are bound in layout by names, like `on_click="button1_click"`
+    ui.register_handler("button1_click", Box::new(button1_click));
+    ui.register_handler("button2_click", Box::new(button2_click));
+    ui.register_handler("button9_click", Box::new(button9_click));
+    // Views are bound to values of model in layout, like `text="{status}"`
+    ui.set_value("status", "Some very very very long text to edit and test clipping");
+    ui.set_layout(layout).unwrap();
+    if let Err(e) = ui.load_theme("themes/ocean.xml") {
+        println!("Error loading theme: {}", e);
     }
-
-    if let Some(button) = ui.get_view("btn2") {
-        button.borrow_mut().on_event(EventType::Click, Box::new(button2_click));
+    for warning in ui.get_warnings() {
+        println!("Warning: {}", warning);
     }
+    // While developing, the layout is loaded again after every change of the file
+    if cfg!(debug_assertions) {
+        ui.watch_layout(concat!(env!("CARGO_MANIFEST_DIR"), "/examples/layout.xml"));
+    }
+
+    // Listeners can be set by ids too, they work after reloading the layout as well
+    ui.on_event("btn3", EventType::Click, Box::new(button3_click));
 
     ui.on_start(Box::new(on_start));
 
@@ -66,24 +78,15 @@ fn main() {
     let options = WindowCreationOptions::new_windowed(window_size, Some(WindowPosition::Center));
     let window: Window<WinEvent> = Window::new_with_user_events(TITLE, options).unwrap();
     let sender = window.create_user_event_sender();
-    let win = Win::new(ui, sender);
+    let win = Win::with_theme(ui, sender, "Classic", ClassicFactory);
     window.run_loop(win);
 }
 
 fn button1_click(ui: &mut UI, view: &dyn View) -> bool {
-    let mut checked = false;
-    if let Some(checkbox) = ui.get_view("checkbox1") {
-        if let Some(ch) = checkbox.borrow_mut().downcast_mut::<CheckBox>() {
-            checked = ch.is_checked();
-        }
-    }
+    // The checkbox and the edit are bound to these values
+    let checked = ui.get_model().get_bool("settings.awesome").unwrap_or(false);
+    ui.set_value("status", &format!("CheckBox checked = {}", checked));
 
-    // Change something in another view
-    if let Some(edit) = ui.get_view("edit1") {
-        if let Some(e) = edit.borrow_mut().downcast_mut::<Edit>() {
-            e.set_text(&format!("CheckBox checked = {}", checked));
-        }
-    }
     // Change something in clicked view
     if let Some(button) = view.as_any().downcast_ref::<Button>() {
         button.set_text("Clicked!");
@@ -105,6 +108,30 @@ fn button2_click(ui: &mut UI, _view: &dyn View) -> bool {
     true
 }
 
+fn button3_click(ui: &mut UI, view: &dyn View) -> bool {
+    // Switch between light, dark and loaded from assets themes
+    let (theme, text) = match ui.get_theme() {
+        "Classic" => ("Dark", "Ocean theme"),
+        "Dark" => ("Ocean", "Light theme"),
+        _ => ("Classic", "Dark theme")
+    };
+    if let Some(button) = view.as_any().downcast_ref::<Button>() {
+        button.set_text(text);
+    }
+    ui.set_theme(theme)
+}
+
+fn button9_click(ui: &mut UI, _view: &dyn View) -> bool {
+    // Switch between locales, texts from `@string/...` change by themselves
+    let locale = match ui.get_locale() {
+        "" => "sk",
+        "sk" => "ru",
+        _ => ""
+    };
+    ui.set_locale(locale);
+    true
+}
+
 fn on_start(ui: &mut UI) {
     let mut buf = Vec::new();
     for i in 1..=20 {
@@ -1,14 +1,29 @@
-use speedy2d::font::Font;
+use speedy2d::font::{Font, FormattedTextBlock, TextLayout, TextOptions};
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::rc::{Rc, Weak};
+use gui::render::Image;
+use gui::themes::Typeface;
 
 pub trait AssetsProvider {
     fn get_file(&self, path: &str) -> Option<&[u8]>;
 }
 
+/// Everything needed to render a laid out text block without the GPU.
+#[derive(Clone)]
+pub struct TextSource {
+    pub chars: Vec<char>,
+    pub font_name: String,
+    pub font_style: String,
+    pub size: f32
+}
+
 thread_local! {
     static PROVIDER: RefCell<Option<Box<dyn AssetsProvider>>> = RefCell::new(None);
     static FONTS: RefCell<HashMap<(String, String), Font>> = RefCell::new(HashMap::new());
+    static GLYPH_FONTS: RefCell<HashMap<(String, String), rusttype::Font<'static>>> = RefCell::new(HashMap::new());
+    static IMAGES: RefCell<HashMap<String, Rc<Image>>> = RefCell::new(HashMap::new());
+    static TEXTS: RefCell<HashMap<usize, (Weak<FormattedTextBlock>, TextSource)>> = RefCell::new(HashMap::new());
 }
 
 pub fn set_provider(value: Box<impl AssetsProvider + 'static>) {
@@ -17,18 +32,55 @@ pub fn set_provider(value: Box<impl AssetsProvider + 'static>) {
     });
 }
 
+/// Reads the whole file from the assets provider
+pub fn get_file(path: &str) -> Option<Vec<u8>> {
+    PROVIDER.with(|provider| {
+        match provider.borrow().as_ref() {
+            None => None,
+            Some(p) => p.get_file(path).map(|bytes| bytes.to_vec())
+        }
+    })
+}
+
+/// Gets the image decoded from the file in assets, images are decoded only once
+pub fn get_image(path: &str) -> Option<Rc<Image>> {
+    if let Some(image) = IMAGES.with(|images| images.borrow().get(path).cloned()) {
+        return Some(image);
+    }
+    let bytes = get_file(path)?;
+    match Image::decode(&bytes) {
+        None => {
+            println!("Error decoding image {} from assets!", path);
+            None
+        }
+        Some(image) => {
+            let image = Rc::new(image);
+            IMAGES.with(|images| images.borrow_mut().insert(path.to_owned(), Rc::clone(&image)));
+            Some(image)
+        }
+    }
+}
+
+fn font_key(name: &str, style: &str) -> (String, String) {
+    (name.replace(" ", ""), style.replace(" ", ""))
+}
+
+fn font_path(key: &(String, String)) -> String {
+    let separator = std::path::MAIN_SEPARATOR;
+    format!("fonts{}{}-{}.ttf", separator, &key.0, &key.1)
+}
+
 pub fn get_font(name: &str, style: &str) -> Option<Font> {
     let mut result = None;
     PROVIDER.with(|provider| {
         match provider.borrow().as_ref() {
             None => {},
             Some(p) => {
-                let key = (name.replace(" ", ""), style.replace(" ", ""));
+                let key = font_key(name, style);
 
                 FONTS.with(|fonts| {
                     if !fonts.borrow().contains_key(&key) {
-                        let separator = std::path::MAIN_SEPARATOR;
-                        if let Some(bytes) = p.get_file(&format!("fonts{}{}-{}.ttf", separator, &key.0, &key.1)) {
+                        if let Some(bytes) = p.get_file(&font_path(&key)) {
                             match Font::new(bytes) {
                                 Ok(font) => {
                                     fonts.borrow_mut().insert(key.clone(), font);
@@ -46,4 +98,91 @@ pub fn get_font(name: &str, style: &str) -> Option<Font> {
     });
 
     result
-}
\ No newline at end of file
+}
+
+/// Gets the same font as `get_font()`, but in a form that can rasterize glyphs on the CPU
+pub fn get_glyph_font(name: &str, style: &str) -> Option<rusttype::Font<'static>> {
+    let mut result = None;
+    PROVIDER.with(|provider| {
+        if let Some(p) = provider.borrow().as_ref() {
+            let key = font_key(name, style);
+
+            GLYPH_FONTS.with(|fonts| {
+                if !fonts.borrow().contains_key(&key) {
+                    if let Some(bytes) = p.get_file(&font_path(&key)) {
+                        match rusttype::Font::try_from_vec(bytes.to_vec()) {
+                            Some(font) => {
+                                fonts.borrow_mut().insert(key.clone(), font);
+                            }
+                            None => {
+                                println!("Error parsing font file from assets!");
+                            }
+                        }
+                    }
+                }
+                result = fonts.borrow().get(&key).cloned()
+            })
+        }
+    });
+
+    result
+}
+
+/// Lays out the text with the font of given typeface.
+/// Views should use this instead of `Font::layout_text()`, as it remembers
+/// where the text block came from, and software renderers need that to draw glyphs.
+pub fn layout_text(typeface: &Typeface, text: &str, size: f32, options: TextOptions) -> Option<Rc<FormattedTextBlock>> {
+    let font_style = typeface.font_style.to_string();
+    let font = get_font(&typeface.font_name, &font_style)?;
+    let chars = text.chars().collect::<Vec<_>>();
+    let block = font.layout_text_from_unindexed_codepoints(&chars, size, options);
+    let source = TextSource { chars, font_name: typeface.font_name.clone(), font_style, size };
+    TEXTS.with(|texts| {
+        let mut texts = texts.borrow_mut();
+        // Forget sources of the texts that are already dropped
+        texts.retain(|_, (weak, _)| weak.strong_count() > 0);
+        texts.insert(Rc::as_ptr(&block) as usize, (Rc::downgrade(&block), source));
+    });
+    Some(block)
+}
+
+/// Gets the source of the text block, if it was laid out by `layout_text()`
+pub fn get_text_source(text: &Rc<FormattedTextBlock>) -> Option<TextSource> {
+    TEXTS.with(|texts| {
+        match texts.borrow().get(&(Rc::as_ptr(text) as usize)) {
+            Some((weak, source)) if weak.strong_count() > 0 => Some(source.clone()),
+            _ => None
+        }
+    })
+}
+
+/// Assets of examples, read from disk, for tests that need real fonts
+#[cfg(test)]
+pub fn set_example_assets() {
+    set_test_assets(&[]);
+}
+
+/// Assets of examples with given files, that tests load by their paths
+#[cfg(test)]
+pub fn set_test_assets(extra: &[(&str, &str)]) {
+    struct Files {
+        files: HashMap<String, Vec<u8>>
+    }
+
+    impl AssetsProvider for Files {
+        fn get_file(&self, path: &str) -> Option<&[u8]> {
+            self.files.get(&path.replace('\\', "/")).map(|bytes| bytes.as_slice())
+        }
+    }
+
+    let fonts = concat!(env!("CARGO_MANIFEST_DIR"), "/examples/assets/fonts");
+    let mut files = HashMap::new();
+    for entry in std::fs::read_dir(fonts).unwrap() {
+        let entry = entry.unwrap();
+        files.insert(format!("fonts/{}", entry.file_name().to_string_lossy()), std::fs::read(entry.path()).unwrap());
+    }
+    for (path, text) in extra {
+        files.insert(path.to_string(), text.as_bytes().to_vec());
+    }
+    set_provider(Box::new(Files { files }));
+}
@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+use quick_xml::events::BytesStart;
 use rand::Rng;
 
 pub const DEFAULT_TEXT_SIZE: f32 = 24_f32;
@@ -47,4 +49,35 @@ pub fn delete_char(text: &str, pos: usize) -> String {
     let part2 = text.chars().skip(pos + 1).collect::<String>();
     part1.push_str(&part2);
     part1
-}
\ No newline at end of file
+}
+
+/// Gets all attributes of XML element as pairs of name and value
+pub fn get_attributes(e: &BytesStart) -> Vec<(String, String)> {
+    e.attributes()
+        .filter_map(|a| a.ok())
+        .map(|a| {
+            let name = String::from_utf8_lossy(a.key.0).to_string();
+            let value = match a.value {
+                Cow::Borrowed(c) => String::from_utf8_lossy(c).to_string(),
+                Cow::Owned(c) => String::from_utf8_lossy(&c).to_string()
+            };
+            (name, value)
+        })
+        .collect()
+}
+
+/// Gets all attributes of XML element like `get_attributes()`, but with unescaped values.
+/// Values that can not be unescaped are kept as they are.
+pub fn get_unescaped_attributes(e: &BytesStart) -> Vec<(String, String)> {
+    e.attributes()
+        .filter_map(|a| a.ok())
+        .map(|a| {
+            let name = String::from_utf8_lossy(a.key.0).to_string();
+            let value = match a.unescape_value() {
+                Ok(value) => value.to_string(),
+                Err(_) => String::from_utf8_lossy(&a.value).to_string()
+            };
+            (name, value)
+        })
+        .collect()
+}
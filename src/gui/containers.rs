@@ -1,64 +1,73 @@
-use std::cell::RefCell;
+use std::cell::{RefCell, RefMut};
+use std::fmt::{self, Display, Formatter};
 use std::rc::Rc;
+use std::str::FromStr;
 
 use speedy2d::dimen::Vector2;
 use speedy2d::window::{KeyScancode, ModifiersState, MouseButton, VirtualKeyCode};
 use super::events::EventType;
+use super::properties::{Property, PropertyType, with_defaults};
 use super::views::Borders;
 
-use super::themes::{FontStyle, Theme, Typeface, ViewState};
+use super::themes::{Theme, Typeface, ViewState};
 use super::traits::{Container, Element, View, WeakElement};
 use super::types::{Point, Rect, rect};
 use super::ui::UI;
-use super::views::{Dimension, Direction, FieldsMain};
+use super::views::{Align, Dimension, Direction, FieldsMain, Gravity, GridCell, main_properties, parse_value};
 
 pub struct Frame {
     state: RefCell<FieldsMain>,
     direction: Direction,
+    /// Placement of views along the direction
+    align: Align,
     views: Vec<Element>,
-    breaking: bool
+    breaking: bool,
+    /// Places of views from the last measure, aligned in the measured size
+    places: Vec<Rect<i32>>,
+    /// Size that views take along the direction
+    used: i32
 }
 
-impl Frame {
-    pub(crate) fn focus_next(&self) -> bool {
-        let mut focused = -1;
-        for i in 0..self.views.len() {
-            let v = &self.views[i];
-            if v.borrow().is_focused() {
-                focused = i as i32;
-                continue;
-            }
-            if let Some(state) = v.borrow().get_state() {
-                if state.focusable && focused >= 0 {
-                    let previous = &self.views[focused as usize];
-                    previous.borrow().set_focused(false);
-                    v.borrow().set_focused(true);
-                    return true;
-                }
+/// Moves focus from the focused view to the next focusable one
+fn focus_next(views: &[Element]) -> bool {
+    let mut focused = -1;
+    for i in 0..views.len() {
+        let v = &views[i];
+        if v.borrow().is_focused() {
+            focused = i as i32;
+            continue;
+        }
+        if let Some(state) = v.borrow().get_state() {
+            if state.focusable && focused >= 0 {
+                let previous = &views[focused as usize];
+                previous.borrow().set_focused(false);
+                v.borrow().set_focused(true);
+                return true;
             }
         }
-        false
     }
+    false
+}
 
-    pub(crate) fn focus_prev(&self) -> bool {
-        let mut focused = -1;
-        for i in (0..self.views.len()).rev() {
-            let v = &self.views[i];
-            if v.borrow().is_focused() {
-                focused = i as i32;
-                continue;
-            }
-            if let Some(state) = v.borrow().get_state() {
-                if state.focusable && focused >= 0 {
-                    let previous = &self.views[focused as usize];
-                    previous.borrow().set_focused(false);
-                    v.borrow().set_focused(true);
-                    return true;
-                }
+/// Moves focus from the focused view to the previous focusable one
+fn focus_prev(views: &[Element]) -> bool {
+    let mut focused = -1;
+    for i in (0..views.len()).rev() {
+        let v = &views[i];
+        if v.borrow().is_focused() {
+            focused = i as i32;
+            continue;
+        }
+        if let Some(state) = v.borrow().get_state() {
+            if state.focusable && focused >= 0 {
+                let previous = &views[focused as usize];
+                previous.borrow().set_focused(false);
+                v.borrow().set_focused(true);
+                return true;
             }
         }
-        false
     }
+    false
 }
 
 impl Frame {
@@ -68,36 +77,251 @@ impl Frame {
         Frame {
             state: RefCell::new(main),
             direction: Direction::default(),
+            align: Align::default(),
             views: Vec::new(),
-            breaking: false
+            breaking: false,
+            places: Vec::new(),
+            used: 0
         }
     }
 
-    fn set_font(&mut self, font_name: &str) {
-        let typeface = match self.state.borrow_mut().typeface.take() {
-            None => Typeface { font_name: font_name.to_owned(), font_style: FontStyle::Regular },
-            Some(mut t) => {
-                t.font_name = font_name.to_owned();
-                t
+    fn set_direction(&mut self, direction: Direction) {
+        self.direction = direction;
+    }
+
+    /// Measures views one after another along the direction, weighted views share the space left by others,
+    /// or shrink from their own sizes by their weights, if others overflow the line.
+    /// Places of views start from the beginning of the line, and the size they take along the direction
+    /// is kept in `used`, so that `align_places()` moves them by `align` and gravity later.
+    /// Returns the size that views take together, with their margins.
+    fn measure_line(&mut self, inner: (i32, i32), typeface: &Typeface, scale: f64) -> (i32, i32) {
+        let horizontal = self.direction == Direction::Horizontal;
+        let weights = self.views.iter()
+            .map(|v| v.borrow().get_main().map(|m| m.weight.max(0f32)).unwrap_or(0f32))
+            .collect::<Vec<_>>();
+        let mut sizes = vec![(0, 0); self.views.len()];
+        let mut extents = vec![0; self.views.len()];
+
+        // Views without weights take the space in their order, views with weights share what is left
+        let mut used = 0;
+        for (i, v) in self.views.iter().enumerate() {
+            if weights[i] > 0f32 {
+                continue;
+            }
+            let left = match self.direction {
+                Direction::Horizontal => (inner.0 - used, inner.1),
+                Direction::Vertical => (inner.0, inner.1 - used)
+            };
+            sizes[i] = measure_view(&mut *v.try_borrow_mut().unwrap(), left, inner, typeface, scale);
+            extents[i] = if horizontal { sizes[i].0 } else { sizes[i].1 };
+            used += extents[i];
+        }
+        let main_size = if horizontal { inner.0 } else { inner.1 };
+        let overflow = used - main_size;
+        let mut free = (main_size - used).max(0);
+        let weights_sum: f32 = weights.iter().sum();
+        let mut weights_left = weights_sum;
+        for (i, v) in self.views.iter().enumerate() {
+            if weights[i] == 0f32 {
+                continue;
             }
+            let share = if overflow > 0 {
+                // Views without weights overflow the line, so weighted views shrink from their own sizes by their weights
+                let mut v = v.try_borrow_mut().unwrap();
+                let (bound_width, bound_height) = v.get_bounds();
+                let wrap = |bound: Dimension| Some(Dimension::Min).filter(|_| matches!(bound, Dimension::Max));
+                let own = if horizontal { (wrap(bound_width), None) } else { (None, wrap(bound_height)) };
+                let (width, height) = measure_as(&mut *v, own, inner, inner, typeface, scale);
+                let natural = if horizontal { width } else { height };
+                (natural - (overflow as f32 * weights[i] / weights_sum).round() as i32).max(0)
+            } else {
+                // Rounding leftovers go to the last weighted view
+                let share = (free as f32 * weights[i] / weights_left).round() as i32;
+                free -= share;
+                weights_left -= weights[i];
+                share
+            };
+            let space = if horizontal { (share, inner.1) } else { (inner.0, share) };
+            let fill = if horizontal { (Some(Dimension::Max), None) } else { (None, Some(Dimension::Max)) };
+            sizes[i] = measure_as(&mut *v.try_borrow_mut().unwrap(), fill, space, inner, typeface, scale);
+            // Weighted views take their shares even if they don't fill them
+            extents[i] = share;
+        }
+
+        // Frames that wrap their content across the direction take the size of views without filling,
+        // and then the filling views are measured again with this size
+        let (bound_width, bound_height) = self.get_bounds();
+        let fills = |v: &Element| match v.borrow().get_bounds() {
+            (Dimension::Max, _) if !horizontal => true,
+            (_, Dimension::Max) if horizontal => true,
+            _ => false
         };
-        self.state.borrow_mut().typeface = Some(typeface)
+        let wraps = matches!(if horizontal { bound_height } else { bound_width }, Dimension::Min);
+        if wraps && self.views.iter().any(fills) {
+            let mut cross = 0;
+            for (i, v) in self.views.iter().enumerate() {
+                let size = match fills(v) {
+                    true => {
+                        let space = if horizontal { (extents[i], inner.1) } else { (inner.0, extents[i]) };
+                        let wrap = if horizontal { (None, Some(Dimension::Min)) } else { (Some(Dimension::Min), None) };
+                        measure_as(&mut *v.try_borrow_mut().unwrap(), wrap, space, inner, typeface, scale)
+                    }
+                    false => sizes[i]
+                };
+                cross = cross.max(if horizontal { size.1 } else { size.0 });
+            }
+            for (i, v) in self.views.iter().enumerate() {
+                if fills(v) {
+                    let space = if horizontal { (extents[i], cross) } else { (cross, extents[i]) };
+                    let weighted = weights[i] > 0f32;
+                    let fill = match (horizontal, weighted) {
+                        (true, true) => (Some(Dimension::Max), None),
+                        (false, true) => (None, Some(Dimension::Max)),
+                        _ => (None, None)
+                    };
+                    sizes[i] = measure_as(&mut *v.try_borrow_mut().unwrap(), fill, space, space, typeface, scale);
+                }
+            }
+        }
+
+        self.places.clear();
+        let padding = self.get_padding(scale);
+        let mut position = 0;
+        let mut cross = 0;
+        for (i, v) in self.views.iter().enumerate() {
+            let margins = v.borrow().get_margin(scale);
+            let (x, y) = match self.direction {
+                Direction::Horizontal => (padding.left + position + margins.left, padding.top + margins.top),
+                Direction::Vertical => (padding.left + margins.left, padding.top + position + margins.top)
+            };
+            let (width, height) = (sizes[i].0 - margins.left - margins.right, sizes[i].1 - margins.top - margins.bottom);
+            self.places.push(rect((x, y), (x + width, y + height)));
+            position += extents[i];
+            cross = cross.max(if horizontal { sizes[i].1 } else { sizes[i].0 });
+        }
+        self.used = position;
+        match self.direction {
+            Direction::Horizontal => (position, cross),
+            Direction::Vertical => (cross, position)
+        }
     }
 
-    fn set_font_style(&mut self, style: &str) {
-        let font_style = FontStyle::from(style);
-        let typeface = match self.state.borrow_mut().typeface.take() {
-            None => Typeface { font_name: String::new(), font_style },
-            Some(t) => Typeface { font_name: t.font_name, font_style },
-        };
-        self.state.borrow_mut().typeface = Some(typeface)
+    /// Measures views in horizontal lines, moving views that don't fit to the next line.
+    /// Returns the size that views take together, with their margins.
+    fn measure_lines(&mut self, inner: (i32, i32), typeface: &Typeface, scale: f64) -> (i32, i32) {
+        self.places.clear();
+        let padding = self.get_padding(scale);
+        let (mut x, mut y, mut width, mut line_height) = (0, 0, 0, 0);
+        for v in self.views.iter() {
+            let mut v = v.try_borrow_mut().unwrap();
+            let margins = v.get_margin(scale);
+            // Views are measured with all the line, so that they are measured once
+            let (w, h) = measure_view(&mut *v, (inner.0, inner.1 - y), inner, typeface, scale);
+            if x > 0 && x + w > inner.0 {
+                y += line_height;
+                x = 0;
+                line_height = 0;
+            }
+            let (left, top) = (padding.left + x + margins.left, padding.top + y + margins.top);
+            let (right, bottom) = (left + w - margins.left - margins.right, top + h - margins.top - margins.bottom);
+            self.places.push(rect((left, top), (right, bottom)));
+            x += w;
+            width = width.max(x);
+            line_height = line_height.max(h);
+            if v.is_break() {
+                y += line_height;
+                x = 0;
+                line_height = 0;
+            }
+        }
+        self.used = 0;
+        (width, y + line_height)
     }
 
-    fn set_direction(&mut self, direction: Direction) {
-        self.direction = direction;
+    /// Gets places of views in the frame of its measured size, moving them along the direction by `align`,
+    /// and across it by their gravity. Stretched views are measured again to fill the frame across.
+    fn align_places(&self, (width, height): (i32, i32), typeface: &Typeface, scale: f64) -> Vec<Rect<i32>> {
+        let padding = self.get_padding(scale);
+        let inner = (width - padding.left - padding.right, height - padding.top - padding.bottom);
+        let horizontal = self.direction == Direction::Horizontal;
+        let (main_size, cross_size) = if horizontal { inner } else { (inner.1, inner.0) };
+        let free = (main_size - self.used).max(0);
+        let count = self.views.len() as i32;
+        let mut places = Vec::new();
+        for (i, (v, place)) in self.views.iter().zip(self.places.iter()).enumerate() {
+            let mut v = v.try_borrow_mut().unwrap();
+            let shift = match self.align {
+                Align::Start => 0,
+                Align::Center => free / 2,
+                Align::End => free,
+                Align::SpaceBetween if count > 1 => free * i as i32 / (count - 1),
+                Align::SpaceBetween => 0
+            };
+            let (gravity, weighted) = v.get_main().map(|m| (m.gravity, m.weight > 0f32)).unwrap_or_default();
+            let margins = v.get_margin(scale);
+            let mut place = *place;
+            if gravity == Gravity::Stretch {
+                // Weighted views fill their shares along the direction, so they keep filling them
+                let space = match self.direction {
+                    Direction::Horizontal => (place.width() + margins.left + margins.right, cross_size),
+                    Direction::Vertical => (cross_size, place.height() + margins.top + margins.bottom)
+                };
+                let fill = (Some(Dimension::Max).filter(|_| !horizontal || weighted), Some(Dimension::Max).filter(|_| horizontal || weighted));
+                let (w, h) = measure_as(&mut *v, fill, space, inner, typeface, scale);
+                place.max.x = place.min.x + w - margins.left - margins.right;
+                place.max.y = place.min.y + h - margins.top - margins.bottom;
+            }
+            let extent = match self.direction {
+                Direction::Horizontal => place.height() + margins.top + margins.bottom,
+                Direction::Vertical => place.width() + margins.left + margins.right
+            };
+            let cross = match gravity {
+                Gravity::Start | Gravity::Stretch => 0,
+                Gravity::Center => ((cross_size - extent) / 2).max(0),
+                Gravity::End => (cross_size - extent).max(0)
+            };
+            match self.direction {
+                Direction::Horizontal => place.move_by((shift, cross)),
+                Direction::Vertical => place.move_by((cross, shift))
+            }
+            places.push(place);
+        }
+        places
     }
 }
 
+/// Measures the view in the space that is left for it, but percents are taken from all the inner size of parent.
+/// Returns the size of the view with its margins.
+fn measure_view(view: &mut dyn View, left: (i32, i32), inner: (i32, i32), typeface: &Typeface, scale: f64) -> (i32, i32) {
+    let (bound_width, bound_height) = view.get_bounds();
+    let width = match bound_width {
+        Dimension::Percent(_) => inner.0,
+        _ => left.0
+    };
+    let height = match bound_height {
+        Dimension::Percent(_) => inner.1,
+        _ => left.1
+    };
+    let margins = view.get_margin(scale);
+    let (width, height) = view.measure(width, height, typeface, scale);
+    (width + margins.left + margins.right, height + margins.top + margins.bottom)
+}
+
+/// Measures the view with other dimensions in place of its own, that are kept
+fn measure_as(view: &mut dyn View, bounds: (Option<Dimension>, Option<Dimension>), space: (i32, i32), inner: (i32, i32), typeface: &Typeface, scale: f64) -> (i32, i32) {
+    let (bound_width, bound_height) = view.get_bounds();
+    if let Some(width) = bounds.0 {
+        view.set_width(width);
+    }
+    if let Some(height) = bounds.1 {
+        view.set_height(height);
+    }
+    let size = measure_view(view, space, inner, typeface, scale);
+    view.set_width(bound_width);
+    view.set_height(bound_height);
+    size
+}
+
 impl Container for Frame {
     fn add_view(&mut self, view: Element) {
         self.views.push(view);
@@ -123,35 +347,40 @@ impl Container for Frame {
     fn get_view_count(&self) -> usize {
         self.views.len()
     }
+
+    fn get_views(&self) -> Vec<Element> {
+        self.views.clone()
+    }
 }
 
 impl View for Frame {
-    fn set_any(&mut self, name: &str, value: &str) {
+    fn set_any(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "direction" => { self.set_direction(parse_value(value)?) }
+            "align" => { self.align = parse_value(value)? }
+            "breaking" => { self.breaking = parse_value(value)? }
+            _ => self.state.borrow_mut().set_any(name, value)?
+        }
+        Ok(())
+    }
+
+    fn get_any(&self, name: &str) -> Option<String> {
         match name {
-            "left" => { self.set_x(value.parse().unwrap()) }
-            "top" => { self.set_y(value.parse().unwrap()) }
-            "width" => { self.set_width(value.parse().unwrap()) }
-            "height" => { self.set_height(value.parse().unwrap()) }
-            "padding" => { self.state.borrow_mut().padding.set_all(value.parse().unwrap_or(0)) }
-            "padding_top" => { self.state.borrow_mut().padding.top = value.parse().unwrap_or(0) }
-            "padding_left" => { self.state.borrow_mut().padding.left = value.parse().unwrap_or(0) }
-            "padding_right" => { self.state.borrow_mut().padding.right = value.parse().unwrap_or(0) }
-            "padding_bottom" => { self.state.borrow_mut().padding.bottom = value.parse().unwrap_or(0) }
-            "margin" => { self.state.borrow_mut().margin.set_all(value.parse().unwrap_or(0)) }
-            "margin_top" => { self.state.borrow_mut().margin.top = value.parse().unwrap_or(0) }
-            "margin_left" => { self.state.borrow_mut().margin.left = value.parse().unwrap_or(0) }
-            "margin_right" => { self.state.borrow_mut().margin.right = value.parse().unwrap_or(0) }
-            "margin_bottom" => { self.state.borrow_mut().margin.bottom = value.parse().unwrap_or(0) }
-            "direction" => { self.set_direction(value.parse().unwrap()) }
-            "id" => { self.set_id(value) }
-            "font" => { self.set_font(value) }
-            "font_style" => { self.set_font_style(value) }
-            "breaking" => { self.breaking = value.parse().unwrap_or(false) }
-            "break" => { self.state.borrow_mut().break_line = value.parse().unwrap_or(false) }
-            &_ => {}
+            "direction" => Some(self.direction.to_string()),
+            "align" => Some(self.align.to_string()),
+            "breaking" => Some(self.breaking.to_string()),
+            _ => self.state.borrow().get_any(name)
         }
     }
 
+    fn properties() -> Vec<Property> {
+        let mut properties = with_defaults(main_properties(), &[("width", "max")]);
+        properties.push(Property::new("direction", PropertyType::Direction, "horizontal", "Direction to place views in"));
+        properties.push(Property::new("align", PropertyType::Align, "start", "Placement of views along the direction, if they leave free space"));
+        properties.push(Property::new("breaking", PropertyType::Bool, "false", "Moves views that do not fit to the next line"));
+        properties
+    }
+
     fn set_parent(&self, parent: Option<WeakElement>) {
         self.state.borrow_mut().parent = parent;
     }
@@ -168,75 +397,31 @@ impl View for Frame {
         }
     }
 
-    fn layout_content(&mut self, x: i32, y: i32, width: i32, height: i32, typeface: &Typeface, scale: f64) -> Rect<i32> {
+    fn measure(&mut self, width: i32, height: i32, typeface: &Typeface, scale: f64) -> (i32, i32) {
         self.state.borrow_mut().scale = scale;
-        //println!("Laying out for {},{} - {},{}", x, y, width, height);
-        let (new_width, new_height) = self.calculate_size(width, height, scale);
-        //println!("New width {}, new height {}", new_width, new_height);
-
+        let typeface = self.state.borrow().get_typeface(typeface);
+        self.state.borrow_mut().resolved_typeface = Some(typeface.clone());
+        let (max_width, max_height) = self.calculate_size(width, height, scale);
         let padding = self.get_padding(scale);
-        let mut xx = padding.left;
-        let mut yy = padding.top;
-        let max_x = new_width - padding.right;
-        let mut max_height = 0;
-        let typeface = match self.state.borrow().typeface.clone() {
-            None => typeface.clone(),
-            Some(t) => t
+        let inner = (max_width - padding.left - padding.right, max_height - padding.top - padding.bottom);
+        let (w, h) = match self.breaking && self.direction == Direction::Horizontal {
+            true => self.measure_lines(inner, &typeface, scale),
+            false => self.measure_line(inner, &typeface, scale)
         };
-        for v in self.views.iter() {
-            let mut v = v.try_borrow_mut().unwrap();
-            let margins = v.get_margin(scale);
-            v.layout_content(xx + margins.left, yy + margins.top, new_width - xx - padding.right, new_height - yy - padding.bottom, &typeface, scale);
-            // Get maximum occupied area
-            let (w, h) = v.calculate_full_size(scale);
-            match self.direction {
-                Direction::Horizontal => xx = xx + w + margins.left + margins.right,
-                Direction::Vertical => yy = yy + h + margins.top + margins.bottom
-            }
-            if self.breaking && self.direction == Direction::Horizontal {
-                if xx > max_x {
-                    yy += max_height + margins.top;
-                    xx = padding.left + margins.left;
-                    v.layout_content(xx, yy + margins.top, new_width - xx - padding.right, new_height - yy - padding.bottom, &typeface, scale);
-                    // Get maximum occupied area
-                    let (w, h) = v.calculate_full_size(scale);
-                    xx += w;
-                    max_height = h + margins.bottom;
-                }
-                if v.is_break() {
-                    let (_, h) = v.calculate_full_size(scale);
-                    xx = padding.left;
-                    yy += h + margins.bottom;
-                }
-            }
-            if h > max_height {
-                max_height = h;
-            }
-            //println!("View {} is at rect {:?}", &v.get_id(), &v.get_rect());
+        let content = (w + padding.left + padding.right, h + padding.top + padding.bottom);
+        let size = self.resolve_size(width, height, content, scale);
+        // Breaking frames keep views at the starts of lines
+        if !self.breaking {
+            self.places = self.align_places(size, &typeface, scale);
         }
+        size
+    }
 
-        let (w, h) = self.calculate_full_size(scale);
-        let (width, height) = {
-            let state = self.state.borrow_mut();
-            let ww;
-            let hh;
-            match &state.width {
-                Dimension::Min => ww = w,
-                Dimension::Max => ww = new_width,
-                Dimension::Dip(dip) => ww = *dip as i32,
-                Dimension::Percent(p) => ww = (width as f32 * p / 100f32).round() as i32
-            }
-            match &state.height {
-                Dimension::Min => hh = h,
-                Dimension::Max => hh = new_height,
-                Dimension::Dip(dip) => hh = *dip as i32,
-                Dimension::Percent(p) => hh = (height as f32 * p / 100f32).round() as i32
-            }
-            (ww, hh)
-        };
-        let rect = rect((x, y), (x + width, y + height));
+    fn arrange(&mut self, rect: Rect<i32>, scale: f64) {
         self.set_rect(rect);
-        rect
+        for (v, place) in self.views.iter().zip(self.places.iter()) {
+            v.try_borrow_mut().unwrap().arrange(*place, scale);
+        }
     }
 
     fn fits_in_rect(&self, width: i32, height: i32, scale: f64) -> bool {
@@ -251,7 +436,10 @@ impl View for Frame {
         //println!("Drawing frame {} in rect: {:?}", self.get_id(), &rect);
         theme.push_clip();
         theme.clip_rect(rect);
-        theme.draw_panel_back(rect, self.state.borrow().state);
+        let state = self.state.borrow().state;
+        if !theme.draw_styled_back(rect, state, &self.state.borrow().background) {
+            theme.draw_panel_back(rect, state);
+        }
         theme.draw_panel_body(rect, self.state.borrow().state);
         for v in self.views.iter() {
             let v = v.try_borrow().unwrap();
@@ -366,7 +554,7 @@ impl View for Frame {
     }
 
     fn set_id(&mut self, id: &str) {
-        self.state.borrow_mut().id = id.to_owned();
+        self.state.borrow_mut().set_id(id);
     }
 
     fn get_id(&self) -> String {
@@ -381,6 +569,10 @@ impl View for Frame {
         Some(self as &mut dyn Container)
     }
 
+    fn get_main(&self) -> Option<RefMut<'_, FieldsMain>> {
+        Some(self.state.borrow_mut())
+    }
+
     fn on_event(&mut self, _event: EventType, _func: Box<dyn FnMut(&mut UI, &dyn View) -> bool>) {
         // No op for now
     }
@@ -451,22 +643,22 @@ impl View for Frame {
         }
         if let Some(code) = virtual_key_code {
             if code == VirtualKeyCode::Right && self.direction == Direction::Horizontal {
-                if self.focus_next() {
+                if focus_next(&self.views) {
                     return true;
                 }
             }
             if code == VirtualKeyCode::Left && self.direction == Direction::Horizontal {
-                if self.focus_prev() {
+                if focus_prev(&self.views) {
                     return true;
                 }
             }
             if code == VirtualKeyCode::Up && self.direction == Direction::Vertical {
-                if self.focus_prev() {
+                if focus_prev(&self.views) {
                     return true;
                 }
             }
             if code == VirtualKeyCode::Down && self.direction == Direction::Vertical {
-                if self.focus_next() {
+                if focus_next(&self.views) {
                     return true;
                 }
             }
@@ -503,4 +695,608 @@ impl Default for Frame {
         let rect = rect((0, 0), (400, 300));
         Frame::new(rect, Dimension::Max, Dimension::Min)
     }
-}
\ No newline at end of file
+}
+
+/// Size of a row or a column of `Grid`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TrackSize {
+    /// Fits the largest view in this track, written as `auto` or `min`
+    Auto,
+    /// Device independent pixels, they will be scaled
+    Dip(u32),
+    /// Fraction of the grid size without padding
+    Percent(f32),
+    /// Share of the space that is left from other tracks, written as `*` or `2*`
+    Star(f32)
+}
+
+impl FromStr for TrackSize {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let result = match s {
+            "auto" | "min" => TrackSize::Auto,
+            &_ => {
+                if let Some(weight) = s.strip_suffix('*') {
+                    match weight {
+                        "" => TrackSize::Star(1f32),
+                        _ => TrackSize::Star(weight.parse::<f32>().map_err(|e| format!("{}, expected weights like 2*", e))?)
+                    }
+                } else if let Some(percent) = s.strip_suffix('%') {
+                    TrackSize::Percent(percent.parse::<f32>().map_err(|e| format!("{}, expected percents like 50%", e))?)
+                } else {
+                    TrackSize::Dip(s.parse::<u32>().map_err(|e| format!("{}, expected auto, dips, percents or weights", e))?)
+                }
+            }
+        };
+        Ok(result)
+    }
+}
+
+impl Display for TrackSize {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TrackSize::Auto => write!(f, "auto"),
+            TrackSize::Dip(dip) => write!(f, "{}", dip),
+            TrackSize::Percent(percent) => write!(f, "{}%", percent),
+            TrackSize::Star(weight) if *weight == 1f32 => write!(f, "*"),
+            TrackSize::Star(weight) => write!(f, "{}*", weight)
+        }
+    }
+}
+
+/// Parses sizes of rows or columns, separated by spaces or commas, like `auto * 2* 120 30%`
+pub fn parse_tracks(value: &str) -> Result<Vec<TrackSize>, String> {
+    let mut tracks = Vec::new();
+    for size in value.split(|c: char| c == ',' || c.is_whitespace()).filter(|s| !s.is_empty()) {
+        tracks.push(parse_value::<TrackSize>(size)?);
+    }
+    if tracks.is_empty() {
+        return Err(format!("Wrong value {:?}: expected sizes of tracks like auto * 120 30%", value));
+    }
+    Ok(tracks)
+}
+
+fn tracks_to_string(tracks: &[TrackSize]) -> String {
+    tracks.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(" ")
+}
+
+/// Calculates sizes of tracks in one direction.
+/// Every cell is given as the first track, number of tracks it takes, and the size it needs there,
+/// or `None` if its size depends on the size of the cell.
+/// Star tracks share the space that is left, or fit their views like `auto` if the grid is not stretched.
+fn measure_tracks(tracks: &[TrackSize], cells: &[(usize, usize, Option<i32>)], available: i32, stretch: bool, scale: f64) -> Vec<i32> {
+    let fits = |track: &TrackSize| match track {
+        TrackSize::Auto => true,
+        TrackSize::Star(_) => !stretch,
+        _ => false
+    };
+    let mut sizes = tracks.iter().map(|track| match track {
+        TrackSize::Dip(dip) => (*dip as f64 * scale).round() as i32,
+        TrackSize::Percent(p) => (available as f32 * p / 100f32).round() as i32,
+        TrackSize::Auto | TrackSize::Star(_) => 0
+    }).collect::<Vec<_>>();
+
+    // Views in one track go first, then spanned views enlarge the last fitting track if they still don't fit
+    for &(start, _, size) in cells.iter().filter(|(_, span, _)| *span == 1) {
+        if let Some(size) = size {
+            if fits(&tracks[start]) && size > sizes[start] {
+                sizes[start] = size;
+            }
+        }
+    }
+    for &(start, span, size) in cells.iter().filter(|(_, span, _)| *span > 1) {
+        if let Some(size) = size {
+            let taken: i32 = sizes[start..start + span].iter().sum();
+            if size > taken {
+                if let Some(i) = (start..start + span).rev().find(|&i| fits(&tracks[i])) {
+                    sizes[i] += size - taken;
+                }
+            }
+        }
+    }
+
+    if stretch {
+        let weights: f32 = tracks.iter().map(|t| match t {
+            TrackSize::Star(weight) => *weight,
+            _ => 0f32
+        }).sum();
+        if weights > 0f32 {
+            let mut left = (available - sizes.iter().sum::<i32>()).max(0);
+            let mut weights_left = weights;
+            for (i, track) in tracks.iter().enumerate() {
+                if let TrackSize::Star(weight) = track {
+                    // Rounding leftovers go to the last star track
+                    let size = (left as f32 * weight / weights_left).round() as i32;
+                    sizes[i] = size;
+                    left -= size;
+                    weights_left -= weight;
+                }
+            }
+        }
+    }
+    sizes
+}
+
+/// Container, that places views in cells of rows and columns.
+/// Sizes of them are set by `rows` and `columns` attributes, and views choose their cells
+/// by `row`, `column`, `row_span` and `column_span` attributes.
+/// Views that fill their cells (`max` or percents) do not change sizes of `auto` tracks.
+pub struct Grid {
+    state: RefCell<FieldsMain>,
+    columns: Vec<TrackSize>,
+    rows: Vec<TrackSize>,
+    /// Sizes of columns and rows from the last layout
+    column_sizes: Vec<i32>,
+    row_sizes: Vec<i32>,
+    /// Places of views from the last measure
+    places: Vec<Rect<i32>>,
+    views: Vec<Element>
+}
+
+impl Grid {
+    pub fn new(rect: Rect<i32>, width: Dimension, height: Dimension) -> Grid {
+        let mut main = FieldsMain::with_rect(rect, width, height);
+        main.state.focusable = false;
+        Grid {
+            state: RefCell::new(main),
+            columns: vec![TrackSize::Star(1f32)],
+            rows: vec![TrackSize::Star(1f32)],
+            column_sizes: Vec::new(),
+            row_sizes: Vec::new(),
+            places: Vec::new(),
+            views: Vec::new()
+        }
+    }
+
+    /// Sets sizes of columns, no columns means one column for all space
+    pub fn set_columns(&mut self, columns: Vec<TrackSize>) {
+        self.columns = Grid::or_one_track(columns);
+    }
+
+    /// Sets sizes of rows, no rows means one row for all space
+    pub fn set_rows(&mut self, rows: Vec<TrackSize>) {
+        self.rows = Grid::or_one_track(rows);
+    }
+
+    fn or_one_track(tracks: Vec<TrackSize>) -> Vec<TrackSize> {
+        match tracks.is_empty() {
+            true => vec![TrackSize::Star(1f32)],
+            false => tracks
+        }
+    }
+
+    /// Gets views with their cells, that are moved inside of existing rows and columns
+    fn get_cells(&self) -> Vec<(Element, GridCell)> {
+        let mut result = Vec::new();
+        for v in self.views.iter() {
+            let mut cell = v.borrow().get_main().map(|m| m.cell).unwrap_or_default();
+            cell.row = cell.row.min(self.rows.len() - 1);
+            cell.column = cell.column.min(self.columns.len() - 1);
+            cell.row_span = cell.row_span.clamp(1, self.rows.len() - cell.row);
+            cell.column_span = cell.column_span.clamp(1, self.columns.len() - cell.column);
+            result.push((Rc::clone(v), cell));
+        }
+        result
+    }
+}
+
+impl Container for Grid {
+    fn add_view(&mut self, view: Element) {
+        self.views.push(view);
+    }
+
+    fn get_view(&self, id: &str) -> Option<Element> {
+        if let Some(found) = self.views.iter().find(|&view| view.borrow().get_id() == id) {
+            return Some(Rc::clone(found));
+        }
+
+        for v in self.views.iter() {
+            if let Some(found) = v.borrow().as_container() {
+                let view = found.get_view(id);
+                if view.is_some() {
+                    return view;
+                }
+            }
+        }
+        None
+    }
+
+    fn get_view_count(&self) -> usize {
+        self.views.len()
+    }
+
+    fn get_views(&self) -> Vec<Element> {
+        self.views.clone()
+    }
+}
+
+impl View for Grid {
+    fn set_any(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "columns" => { self.set_columns(parse_tracks(value)?) }
+            "rows" => { self.set_rows(parse_tracks(value)?) }
+            _ => self.state.borrow_mut().set_any(name, value)?
+        }
+        Ok(())
+    }
+
+    fn get_any(&self, name: &str) -> Option<String> {
+        match name {
+            "columns" => Some(tracks_to_string(&self.columns)),
+            "rows" => Some(tracks_to_string(&self.rows)),
+            _ => self.state.borrow().get_any(name)
+        }
+    }
+
+    fn properties() -> Vec<Property> {
+        let mut properties = with_defaults(main_properties(), &[("width", "max")]);
+        properties.push(Property::new("columns", PropertyType::Tracks, "*", "Widths of columns: auto, dips, percents or weights like 2*"));
+        properties.push(Property::new("rows", PropertyType::Tracks, "*", "Heights of rows: auto, dips, percents or weights like 2*"));
+        properties
+    }
+
+    fn set_parent(&self, parent: Option<WeakElement>) {
+        self.state.borrow_mut().parent = parent;
+    }
+
+    fn get_parent(&self) -> Option<Element> {
+        match &self.state.borrow().parent {
+            None => { None }
+            Some(weak) => { weak.upgrade() }
+        }
+    }
+
+    fn measure(&mut self, width: i32, height: i32, typeface: &Typeface, scale: f64) -> (i32, i32) {
+        self.state.borrow_mut().scale = scale;
+        let (max_width, max_height) = self.calculate_size(width, height, scale);
+        let padding = self.get_padding(scale);
+        let inner = (max_width - padding.left - padding.right, max_height - padding.top - padding.bottom);
+        let typeface = self.state.borrow().get_typeface(typeface);
+        self.state.borrow_mut().resolved_typeface = Some(typeface.clone());
+        let (bound_width, bound_height) = self.get_bounds();
+        let cells = self.get_cells();
+
+        // Widths of columns, views are measured in all the space they could take
+        let mut widths = Vec::new();
+        for (v, cell) in cells.iter() {
+            let mut v = v.try_borrow_mut().unwrap();
+            let size = match v.get_bounds().0 {
+                Dimension::Min | Dimension::Dip(_) => Some(measure_view(&mut *v, inner, inner, &typeface, scale).0),
+                _ => None
+            };
+            widths.push((cell.column, cell.column_span, size));
+        }
+        let stretch = !matches!(bound_width, Dimension::Min);
+        self.column_sizes = measure_tracks(&self.columns, &widths, inner.0, stretch, scale);
+
+        // Heights of rows, views are measured in the width of their cells
+        let mut heights = Vec::new();
+        for (v, cell) in cells.iter() {
+            let mut v = v.try_borrow_mut().unwrap();
+            let size = match v.get_bounds().1 {
+                Dimension::Min | Dimension::Dip(_) => {
+                    let cell_width = self.column_sizes[cell.column..cell.column + cell.column_span].iter().sum();
+                    Some(measure_view(&mut *v, (cell_width, inner.1), (cell_width, inner.1), &typeface, scale).1)
+                }
+                _ => None
+            };
+            heights.push((cell.row, cell.row_span, size));
+        }
+        let stretch = !matches!(bound_height, Dimension::Min);
+        self.row_sizes = measure_tracks(&self.rows, &heights, inner.1, stretch, scale);
+
+        // Views are measured in their cells at last, percents are taken from cells
+        self.places.clear();
+        for (v, cell) in cells.iter() {
+            let mut v = v.try_borrow_mut().unwrap();
+            let margins = v.get_margin(scale);
+            let x = padding.left + self.column_sizes[..cell.column].iter().sum::<i32>() + margins.left;
+            let y = padding.top + self.row_sizes[..cell.row].iter().sum::<i32>() + margins.top;
+            let cell_width = self.column_sizes[cell.column..cell.column + cell.column_span].iter().sum();
+            let cell_height = self.row_sizes[cell.row..cell.row + cell.row_span].iter().sum();
+            let (w, h) = measure_view(&mut *v, (cell_width, cell_height), (cell_width, cell_height), &typeface, scale);
+            self.places.push(rect((x, y), (x + w - margins.left - margins.right, y + h - margins.top - margins.bottom)));
+        }
+
+        let (w, h) = self.calculate_full_size(scale);
+        self.resolve_size(width, height, (w, h), scale)
+    }
+
+    fn arrange(&mut self, rect: Rect<i32>, scale: f64) {
+        self.set_rect(rect);
+        for (v, place) in self.views.iter().zip(self.places.iter()) {
+            v.try_borrow_mut().unwrap().arrange(*place, scale);
+        }
+    }
+
+    fn fits_in_rect(&self, width: i32, height: i32, scale: f64) -> bool {
+        let size = self.calculate_full_size(scale);
+        size.0 <= width && size.1 <= height
+    }
+
+    fn paint(&self, origin: Point<i32>, theme: &mut dyn Theme) {
+        let mut rect = self.state.borrow().rect;
+        let start = rect.min + origin;
+        rect.move_by(origin);
+        theme.push_clip();
+        theme.clip_rect(rect);
+        let state = self.state.borrow().state;
+        if !theme.draw_styled_back(rect, state, &self.state.borrow().background) {
+            theme.draw_panel_back(rect, state);
+        }
+        theme.draw_panel_body(rect, self.state.borrow().state);
+        for v in self.views.iter() {
+            let v = v.try_borrow().unwrap();
+            v.paint(start, theme);
+        }
+        theme.pop_clip();
+    }
+
+    fn get_state(&self) -> Option<ViewState> {
+        Some(self.state.borrow().state)
+    }
+
+    fn get_rect(&self) -> Rect<i32> {
+        self.state.borrow().rect
+    }
+
+    fn set_rect(&mut self, rect: Rect<i32>) {
+        self.state.borrow_mut().rect = rect;
+    }
+
+    fn get_padding(&self, scale: f64) -> Borders {
+        self.state.borrow().padding.scaled(scale)
+    }
+
+    fn set_padding(&self, top: i32, left: i32, right: i32, bottom: i32) {
+        let mut state = self.state.borrow_mut();
+        state.padding.top = top;
+        state.padding.left = left;
+        state.padding.right = right;
+        state.padding.bottom = bottom;
+    }
+
+    fn get_margin(&self, scale: f64) -> Borders {
+        self.state.borrow().margin.scaled(scale)
+    }
+
+    fn set_margin(&self, top: i32, left: i32, right: i32, bottom: i32) {
+        let mut state = self.state.borrow_mut();
+        state.margin.top = top;
+        state.margin.left = left;
+        state.margin.right = right;
+        state.margin.bottom = bottom;
+    }
+
+    fn get_bounds(&self) -> (Dimension, Dimension) {
+        let state = self.state.borrow();
+        (state.width, state.height)
+    }
+
+    fn get_content_size(&self) -> (i32, i32) {
+        (self.column_sizes.iter().sum(), self.row_sizes.iter().sum())
+    }
+
+    fn is_focused(&self) -> bool {
+        self.views.iter().any(|v| v.borrow().is_focused())
+    }
+
+    fn is_break(&self) -> bool {
+        self.state.borrow().break_line
+    }
+
+    fn set_focused(&self, focused: bool) {
+        if focused {
+            return;
+        }
+        for v in self.views.iter() {
+            v.borrow().set_focused(false);
+        }
+    }
+
+    fn set_focusable(&self, focusable: bool) {
+        self.state.borrow_mut().state.focusable = focusable;
+    }
+
+    fn set_width(&mut self, width: Dimension) {
+        self.state.borrow_mut().width = width;
+    }
+
+    fn set_height(&mut self, height: Dimension) {
+        self.state.borrow_mut().height = height;
+    }
+
+    fn set_id(&mut self, id: &str) {
+        self.state.borrow_mut().set_id(id);
+    }
+
+    fn get_id(&self) -> String {
+        self.state.borrow().id.clone()
+    }
+
+    fn as_container(&self) -> Option<&dyn Container> {
+        Some(self as &dyn Container)
+    }
+
+    fn as_container_mut(&mut self) -> Option<&mut dyn Container> {
+        Some(self as &mut dyn Container)
+    }
+
+    fn get_main(&self) -> Option<RefMut<'_, FieldsMain>> {
+        Some(self.state.borrow_mut())
+    }
+
+    fn on_event(&mut self, _event: EventType, _func: Box<dyn FnMut(&mut UI, &dyn View) -> bool>) {
+        // No op for now
+    }
+
+    fn click(&self, _ui: &mut UI) -> bool {
+        false
+    }
+
+    fn update(&mut self, ui: &mut UI) -> bool {
+        for v in self.views.iter() {
+            if v.borrow_mut().update(ui) {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn on_mouse_move(&self, ui: &mut UI, position: Vector2<i32>) -> bool {
+        let position = (position.x - self.state.borrow().rect.min.x, position.y - self.state.borrow().rect.min.y);
+        let mut processed = false;
+        for v in self.views.iter().rev() {
+            processed |= v.borrow().on_mouse_move(ui, Vector2::from(position));
+        }
+        processed
+    }
+
+    fn on_mouse_button_down(&self, ui: &mut UI, position: Vector2<i32>, button: MouseButton) -> bool {
+        let position = (position.x - self.state.borrow().rect.min.x, position.y - self.state.borrow().rect.min.y);
+        for v in self.views.iter().rev() {
+            let f = v.borrow().is_focused();
+            if v.borrow().on_mouse_button_down(ui, Vector2::from(position), button) {
+                // If focused changed to true
+                if !f && v.borrow().is_focused() {
+                    for vv in self.views.iter() {
+                        if vv.borrow().get_id() != v.borrow().get_id() {
+                            vv.borrow_mut().set_focused(false);
+                        }
+                    }
+                }
+                return true;
+            }
+        }
+        false
+    }
+
+    fn on_mouse_button_up(&self, ui: &mut UI, position: Vector2<i32>, button: MouseButton) -> bool {
+        let position = (position.x - self.state.borrow().rect.min.x, position.y - self.state.borrow().rect.min.y);
+        for v in self.views.iter().rev() {
+            if v.borrow().on_mouse_button_up(ui, Vector2::from(position), button) {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn on_key_down(&self, ui: &mut UI, virtual_key_code: Option<VirtualKeyCode>, scancode: KeyScancode, state: ModifiersState) -> bool {
+        for v in self.views.iter() {
+            if v.borrow().is_focused() && v.borrow().on_key_down(ui, virtual_key_code, scancode, state.clone()) {
+                return true;
+            }
+        }
+        // Views are added row by row, so arrows move focus in this order
+        match virtual_key_code {
+            Some(VirtualKeyCode::Right) | Some(VirtualKeyCode::Down) => focus_next(&self.views),
+            Some(VirtualKeyCode::Left) | Some(VirtualKeyCode::Up) => focus_prev(&self.views),
+            _ => false
+        }
+    }
+
+    fn on_key_up(&self, ui: &mut UI, virtual_key_code: Option<VirtualKeyCode>, scancode: KeyScancode, state: ModifiersState) -> bool {
+        for v in self.views.iter() {
+            if v.borrow().is_focused() && v.borrow().on_key_up(ui, virtual_key_code, scancode, state.clone()) {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn on_key_char(&self, ui: &mut UI, unicode_codepoint: char, state: ModifiersState) -> bool {
+        for v in self.views.iter() {
+            if v.borrow().is_focused() && v.borrow().on_key_char(ui, unicode_codepoint, state.clone()) {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl Default for Grid {
+    fn default() -> Self {
+        let rect = rect((0, 0), (400, 300));
+        Grid::new(rect, Dimension::Max, Dimension::Min)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gui::containers::{Grid, TrackSize, measure_tracks};
+    use gui::themes::{Recorder, Theme};
+    use gui::traits::View;
+    use gui::types::{Rect, rect};
+    use gui::ui::UI;
+    use gui::views::Dimension;
+
+    /// Gets the rect of the view, relative to its parent
+    fn get_rect(ui: &UI, id: &str) -> Rect<i32> {
+        ui.get_view(id).unwrap().borrow().get_rect()
+    }
+
+    #[test]
+    fn mixed_tracks_share_space() {
+        let tracks = [TrackSize::Auto, TrackSize::Dip(100), TrackSize::Percent(25f32), TrackSize::Star(1f32), TrackSize::Star(3f32)];
+        let cells = [(0, 1, Some(30)), (1, 1, Some(500)), (3, 2, None)];
+        assert_eq!(measure_tracks(&tracks, &cells, 400, true, 1.0), vec![30, 100, 100, 43, 127]);
+        // Star tracks fit their views, if the grid is not stretched
+        let cells = [(0, 1, Some(30)), (3, 1, Some(20)), (3, 2, Some(50))];
+        assert_eq!(measure_tracks(&tracks, &cells, 400, false, 1.0), vec![30, 100, 100, 20, 30]);
+    }
+
+    #[test]
+    fn views_are_placed_in_cells() {
+        let xml = r#"<Grid id="grid" width="max" height="max" columns="auto 100 25% *" rows="50 *">
+            <Frame id="auto" width="30" height="20"/>
+            <Frame id="span" width="max" height="max" column="1" column_span="2"/>
+            <Frame id="star" width="max" height="max" row="1" column="3"/>
+            <Frame id="outside" width="max" height="max" row="5" column="9" row_span="3"/>
+        </Grid>"#;
+        let ui = UI::from_xml(xml, 400, 300, Recorder::typeface()).unwrap();
+        assert_eq!(get_rect(&ui, "auto"), rect((0, 0), (30, 20)));
+        assert_eq!(get_rect(&ui, "span"), rect((30, 0), (230, 50)));
+        assert_eq!(get_rect(&ui, "star"), rect((230, 50), (400, 300)));
+        // Cells outside of the grid are moved to the last row and column
+        assert_eq!(get_rect(&ui, "outside"), rect((230, 50), (400, 300)));
+    }
+
+    #[test]
+    fn empty_tracks_are_one_star_track() {
+        let mut grid = Grid::new(rect((0, 0), (0, 0)), Dimension::Max, Dimension::Max);
+        grid.set_columns(Vec::new());
+        grid.set_rows(Vec::new());
+        assert_eq!(grid.get_any("columns").as_deref(), Some("*"));
+        assert_eq!(grid.get_any("rows").as_deref(), Some("*"));
+
+        let xml = r#"<Grid id="grid" width="max" height="max"><Frame id="all" width="max" height="max" row="1" column="1"/></Grid>"#;
+        let ui = UI::from_xml(xml, 400, 300, Recorder::typeface()).unwrap();
+        assert_eq!(get_rect(&ui, "all"), rect((0, 0), (400, 300)));
+    }
+
+    #[test]
+    fn weighted_views_shrink_when_others_overflow() {
+        let xml = r#"<Frame id="line" width="200" height="max">
+            <Frame id="fixed" width="250" height="max"/>
+            <Frame id="one" width="100" height="max" weight="1"/>
+            <Frame id="three" width="100" height="max" weight="3"/>
+        </Frame>"#;
+        let ui = UI::from_xml(xml, 400, 300, Recorder::typeface()).unwrap();
+        // "fixed" overflows the line by 50, weighted views shrink from 100 by 13 and 38
+        let widths = ["fixed", "one", "three"].map(|id| get_rect(&ui, id).width());
+        assert_eq!(widths, [250, 87, 62]);
+        assert_eq!(get_rect(&ui, "three").min.x, 337);
+    }
+
+    #[test]
+    fn views_are_aligned_in_measured_size() {
+        let xml = r#"<Frame id="line" width="200" height="100" align="center">
+            <Frame id="centered" width="50" height="20" gravity="center"/>
+            <Frame id="stretched" width="30" height="20" gravity="stretch"/>
+        </Frame>"#;
+        let ui = UI::from_xml(xml, 400, 300, Recorder::typeface()).unwrap();
+        assert_eq!(get_rect(&ui, "centered"), rect((60, 40), (110, 60)));
+        assert_eq!(get_rect(&ui, "stretched"), rect((110, 0), (140, 100)));
+    }
+}
@@ -16,4 +16,14 @@ pub enum EventData {
     MouseDown { x: u32, y: u32 },
     MouseMove { x: u32, y: u32 },
     MouseUp { x: u32, y: u32 },
-}
\ No newline at end of file
+}
+impl EventType {
+    /// Gets the event by the name of XML attribute, that binds a handler to it, like `on_click`
+    pub fn from_attribute(name: &str) -> Option<EventType> {
+        match name {
+            "on_click" => Some(EventType::Click),
+            "on_checked_changed" => Some(EventType::CheckedChanged),
+            _ => None
+        }
+    }
+}
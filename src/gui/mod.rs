@@ -6,6 +6,12 @@ pub mod containers;
 pub mod views;
 pub mod win;
 pub mod themes;
+pub mod render;
 pub mod types;
+pub mod properties;
+pub mod resources;
+pub mod reload;
+pub mod model;
 pub mod assets;
-pub mod styles;
\ No newline at end of file
+pub mod styles;
+pub mod snapshot;
\ No newline at end of file
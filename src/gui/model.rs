@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+
+/// Values of application, that views are bound to in layouts like `text="{user.name}"`.
+/// Keys are full names with dots, values are kept as strings, like attributes of views.
+#[derive(Debug, Default)]
+pub struct Model {
+    values: HashMap<String, String>,
+    /// Keys that were changed since the last `take_changed()`
+    changed: Vec<String>
+}
+
+#[allow(dead_code)]
+impl Model {
+    pub fn new() -> Self {
+        Model::default()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(|v| v.as_str())
+    }
+
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        self.get(key)?.parse().ok()
+    }
+
+    /// Sets the value, returns true if it is different from the old one
+    pub fn set(&mut self, key: &str, value: &str) -> bool {
+        if self.get(key) == Some(value) {
+            return false;
+        }
+        self.values.insert(key.to_owned(), value.to_owned());
+        if !self.changed.iter().any(|k| k == key) {
+            self.changed.push(key.to_owned());
+        }
+        true
+    }
+
+    /// Gets all keys, sorted
+    pub fn keys(&self) -> Vec<&str> {
+        let mut keys = self.values.keys().map(|k| k.as_str()).collect::<Vec<_>>();
+        keys.sort_unstable();
+        keys
+    }
+
+    /// Gets keys that were changed since the last call, in order of changes
+    pub fn take_changed(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.changed)
+    }
+}
+
+/// Gets the key of model, if the value of attribute binds to it like `{user.name}`
+pub fn parse_binding(value: &str) -> Option<&str> {
+    let key = value.trim().strip_prefix('{')?.strip_suffix('}')?.trim();
+    let valid = !key.is_empty() && key.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '.');
+    match valid {
+        true => Some(key),
+        false => None
+    }
+}
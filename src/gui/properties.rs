@@ -0,0 +1,118 @@
+use std::fmt;
+use gui::containers::parse_tracks;
+use gui::styles::parse_color;
+use gui::views::{Align, AspectRatio, Dimension, Direction, Gravity, parse_value};
+
+/// Kind of values that a property takes in XML
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PropertyType {
+    Int,
+    /// Number that can have fractional part, like `1.5`
+    Float,
+    Bool,
+    String,
+    /// `min`, `max`, dips like `120` or percents like `50%`
+    Dimension,
+    /// `horizontal` or `vertical`
+    Direction,
+    /// `start`, `center`, `end` or `space-between`
+    Align,
+    /// `start`, `center`, `end` or `stretch`
+    Gravity,
+    /// `Regular`, `Bold`, `Italic` or `BoldItalic`
+    FontStyle,
+    /// `#rrggbb`, `#aarrggbb` or `transparent`
+    Color,
+    /// Name of the handler, that is registered in `UI`
+    Handler,
+    /// Sizes of grid rows or columns, like `auto * 2* 120 30%`
+    Tracks,
+    /// Ratio of width to height, like `16:9` or `1.5`
+    Ratio
+}
+
+impl PropertyType {
+    /// Checks that the value can be parsed as this type
+    pub fn validate(&self, value: &str) -> Result<(), String> {
+        match self {
+            PropertyType::Int => parse_value::<i32>(value).map(|_| ()),
+            PropertyType::Float => parse_value::<f32>(value).map(|_| ()),
+            PropertyType::Bool => parse_value::<bool>(value).map(|_| ()),
+            PropertyType::String | PropertyType::Handler => Ok(()),
+            PropertyType::Dimension => parse_value::<Dimension>(value).map(|_| ()),
+            PropertyType::Direction => parse_value::<Direction>(value).map(|_| ()),
+            PropertyType::Align => parse_value::<Align>(value).map(|_| ()),
+            PropertyType::Gravity => parse_value::<Gravity>(value).map(|_| ()),
+            PropertyType::FontStyle => match value {
+                "Regular" | "Bold" | "Italic" | "BoldItalic" => Ok(()),
+                _ => Err(format!("Wrong value {:?}: expected Regular, Bold, Italic or BoldItalic", value))
+            },
+            PropertyType::Color => match parse_color(value) {
+                Some(_) => Ok(()),
+                None => Err(format!("Wrong value {:?}: expected #rrggbb, #aarrggbb or transparent", value))
+            },
+            PropertyType::Tracks => parse_tracks(value).map(|_| ()),
+            PropertyType::Ratio => parse_value::<AspectRatio>(value).map(|_| ())
+        }
+    }
+}
+
+impl fmt::Display for PropertyType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            PropertyType::Int => "int",
+            PropertyType::Float => "float",
+            PropertyType::Bool => "bool",
+            PropertyType::String => "string",
+            PropertyType::Dimension => "dimension",
+            PropertyType::Direction => "direction",
+            PropertyType::Align => "align",
+            PropertyType::Gravity => "gravity",
+            PropertyType::FontStyle => "font style",
+            PropertyType::Color => "color",
+            PropertyType::Handler => "handler",
+            PropertyType::Tracks => "tracks",
+            PropertyType::Ratio => "ratio"
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Description of one property, that a view type understands in `set_any()`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Property {
+    pub name: &'static str,
+    pub kind: PropertyType,
+    /// Value that the view has when the attribute is not set, empty if it is taken from parent or generated
+    pub default: &'static str,
+    pub description: &'static str
+}
+
+impl Property {
+    pub const fn new(name: &'static str, kind: PropertyType, default: &'static str, description: &'static str) -> Self {
+        Property { name, kind, default, description }
+    }
+
+    pub fn validate(&self, value: &str) -> Result<(), String> {
+        self.kind.validate(value)
+    }
+}
+
+/// Changes defaults of some properties, for views that differ from others
+pub fn with_defaults(mut properties: Vec<Property>, defaults: &[(&str, &'static str)]) -> Vec<Property> {
+    for property in properties.iter_mut() {
+        if let Some((_, default)) = defaults.iter().find(|(name, _)| *name == property.name) {
+            property.default = default;
+        }
+    }
+    properties
+}
+
+/// Makes Markdown table of the properties, for documentation
+pub fn to_markdown(properties: &[Property]) -> String {
+    let mut result = String::from("| Attribute | Type | Default | Description |\n|---|---|---|---|\n");
+    for p in properties {
+        result.push_str(&format!("| `{}` | {} | {} | {} |\n", p.name, p.kind, p.default, p.description));
+    }
+    result
+}
@@ -0,0 +1,76 @@
+use std::rc::Rc;
+use std::time::{Duration, Instant, SystemTime};
+use speedy2d::font::{FormattedTextBlock, TextAlignment, TextOptions};
+use gui::assets::layout_text;
+use gui::themes::{Theme, Typeface};
+use gui::types::rect;
+
+/// Watches the layout file on disk, for reloading it during development.
+/// The file is checked not more often than `INTERVAL`, from the update tick of `UI`.
+pub struct LayoutWatcher {
+    path: String,
+    modified: Option<SystemTime>,
+    checked: Instant,
+    /// Text of the last load error, shown over the views until the file is fixed
+    error: Option<Rc<FormattedTextBlock>>
+}
+
+impl LayoutWatcher {
+    const INTERVAL: Duration = Duration::from_millis(500);
+    const ERROR_BACK: u32 = 0xe0a02020;
+    const ERROR_TEXT: u32 = 0xffffffff;
+    const ERROR_PADDING: i32 = 8;
+
+    pub fn new(path: &str) -> Self {
+        LayoutWatcher { path: path.to_owned(), modified: get_modified(path), checked: Instant::now(), error: None }
+    }
+
+    pub fn get_path(&self) -> &str {
+        &self.path
+    }
+
+    /// Checks if the file was changed since the last check
+    pub fn poll(&mut self) -> bool {
+        if self.checked.elapsed() < LayoutWatcher::INTERVAL {
+            return false;
+        }
+        self.checked = Instant::now();
+        let modified = get_modified(&self.path);
+        if modified == self.modified {
+            return false;
+        }
+        self.modified = modified;
+        true
+    }
+
+    /// Lays out the text of error to show it, or hides it if the message is `None`
+    pub fn set_error(&mut self, message: Option<&str>, typeface: &Typeface, width: u32, scale: f64) {
+        self.error = message.and_then(|message| {
+            let width = width as f32 - (LayoutWatcher::ERROR_PADDING * 2) as f32 * scale as f32;
+            let options = TextOptions::new().with_wrap_to_width(width.max(1.0), TextAlignment::Left);
+            layout_text(typeface, message, 14f32 * scale as f32, options)
+        });
+    }
+
+    pub fn has_error(&self) -> bool {
+        self.error.is_some()
+    }
+
+    /// Paints the error at the top of window, over the views
+    pub fn paint(&self, theme: &mut dyn Theme, width: u32, scale: f64) {
+        if let Some(text) = &self.error {
+            let padding = (LayoutWatcher::ERROR_PADDING as f64 * scale) as i32;
+            let height = text.height().ceil() as i32 + padding * 2;
+            let rect = rect((0, 0), (width as i32, height));
+            theme.push_clip();
+            theme.clip_rect(rect);
+            theme.draw_rect(rect, LayoutWatcher::ERROR_BACK);
+            theme.draw_text(padding as f32, padding as f32, LayoutWatcher::ERROR_TEXT, text);
+            theme.pop_clip();
+        }
+    }
+}
+
+fn get_modified(path: &str) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
@@ -0,0 +1,244 @@
+use std::rc::Rc;
+use image::{ImageResult, RgbaImage};
+use speedy2d::font::FormattedTextBlock;
+use gui::assets::{get_glyph_font, get_text_source};
+use gui::render::{ClipStack, Image, Renderer};
+use gui::types::Rect;
+
+/// In-memory RGBA framebuffer, that is painted by the CPU.
+/// It lets to render UI without any GPU or window.
+pub struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+    clips: ClipStack,
+    clip: Option<Rect<i32>>
+}
+
+#[allow(dead_code)]
+impl Canvas {
+    pub fn new(width: u32, height: u32) -> Self {
+        let pixels = vec![0u8; (width * height * 4) as usize];
+        Canvas { width, height, pixels, clips: ClipStack::new(width as i32, height as i32), clip: None }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Raw pixels, 4 bytes per pixel in RGBA order, row by row
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Gets the color of one pixel as 0xAARRGGBB
+    pub fn get_pixel(&self, x: u32, y: u32) -> u32 {
+        let offset = ((y * self.width + x) * 4) as usize;
+        let p = &self.pixels[offset..offset + 4];
+        (p[3] as u32) << 24 | (p[0] as u32) << 16 | (p[1] as u32) << 8 | p[2] as u32
+    }
+
+    pub fn to_image(&self) -> RgbaImage {
+        RgbaImage::from_raw(self.width, self.height, self.pixels.clone()).unwrap()
+    }
+
+    pub fn save_png(&self, path: &str) -> ImageResult<()> {
+        self.to_image().save_with_format(path, image::ImageFormat::Png)
+    }
+
+    /// Fills every pixel whose center lies inside of the rectangle
+    fn fill_area(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, color: [f32; 4]) {
+        for y in (y1 - 0.5).ceil() as i32..(y2 - 0.5).ceil() as i32 {
+            for x in (x1 - 0.5).ceil() as i32..(x2 - 0.5).ceil() as i32 {
+                self.blend(x, y, color, 1f32);
+            }
+        }
+    }
+
+    /// Mixes the color into the pixel, `coverage` is the part of the pixel that is covered by the shape
+    fn blend(&mut self, x: i32, y: i32, color: [f32; 4], coverage: f32) {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return;
+        }
+        if let Some(clip) = &self.clip {
+            if x < clip.min.x || y < clip.min.y || x >= clip.max.x || y >= clip.max.y {
+                return;
+            }
+        }
+        let alpha = color[3] * coverage.clamp(0f32, 1f32);
+        if alpha <= 0f32 {
+            return;
+        }
+        let offset = ((y as u32 * self.width + x as u32) * 4) as usize;
+        let p = &mut self.pixels[offset..offset + 4];
+        let mix = |src: f32, dst: u8| (src * 255f32 * alpha + dst as f32 * (1f32 - alpha)).round() as u8;
+        p[0] = mix(color[0], p[0]);
+        p[1] = mix(color[1], p[1]);
+        p[2] = mix(color[2], p[2]);
+        p[3] = (alpha * 255f32 + p[3] as f32 * (1f32 - alpha)).round() as u8;
+    }
+}
+
+impl Renderer for Canvas {
+    fn clear_screen(&mut self, color: u32) {
+        let pixel = [(color >> 16) as u8, (color >> 8) as u8, color as u8, (color >> 24) as u8];
+        for p in self.pixels.chunks_exact_mut(4) {
+            p.copy_from_slice(&pixel);
+        }
+    }
+
+    fn fill_rect(&mut self, rect: Rect<i32>, color: u32) {
+        let (x1, y1, x2, y2) = (rect.min.x as f32, rect.min.y as f32, rect.max.x as f32, rect.max.y as f32);
+        self.fill_area(x1, y1, x2, y2, to_floats(color));
+    }
+
+    /// Mixes corner colors for every pixel by its distance to the corners
+    fn fill_gradient(&mut self, rect: Rect<i32>, colors: [u32; 4]) {
+        if rect.width() <= 0 || rect.height() <= 0 {
+            return;
+        }
+        let [top_left, top_right, bottom_right, bottom_left] = colors.map(to_floats);
+        let mix = |a: [f32; 4], b: [f32; 4], t: f32| [0, 1, 2, 3].map(|i| a[i] + (b[i] - a[i]) * t);
+        for y in rect.min.y..rect.max.y {
+            let ty = (y - rect.min.y) as f32 / (rect.height() - 1).max(1) as f32;
+            let left = mix(top_left, bottom_left, ty);
+            let right = mix(top_right, bottom_right, ty);
+            for x in rect.min.x..rect.max.x {
+                let tx = (x - rect.min.x) as f32 / (rect.width() - 1).max(1) as f32;
+                self.blend(x, y, mix(left, right, tx), 1f32);
+            }
+        }
+    }
+
+    /// Fills every pixel whose center lies inside of the line stretched to given thickness
+    fn draw_line(&mut self, start: (f32, f32), end: (f32, f32), thickness: f32, color: u32) {
+        let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+        let length = (dx * dx + dy * dy).sqrt();
+        if length == 0f32 {
+            return;
+        }
+        let color = to_floats(color);
+        let (dx, dy) = (dx / length, dy / length);
+        let half = thickness / 2f32;
+        let min_x = (start.0.min(end.0) - half).floor() as i32;
+        let max_x = (start.0.max(end.0) + half).ceil() as i32;
+        let min_y = (start.1.min(end.1) - half).floor() as i32;
+        let max_y = (start.1.max(end.1) + half).ceil() as i32;
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let px = x as f32 + 0.5 - start.0;
+                let py = y as f32 + 0.5 - start.1;
+                let along = px * dx + py * dy;
+                let across = py * dx - px * dy;
+                if along >= 0f32 && along < length && across >= -half && across < half {
+                    self.blend(x, y, color, 1f32);
+                }
+            }
+        }
+    }
+
+    /// Fills every pixel whose center lies inside of the polygon, by even-odd rule
+    fn fill_polygon(&mut self, points: &[(f32, f32)], color: u32) {
+        if points.len() < 3 {
+            return;
+        }
+        let color = to_floats(color);
+        let min_y = points.iter().map(|p| p.1).fold(f32::MAX, f32::min).floor() as i32;
+        let max_y = points.iter().map(|p| p.1).fold(f32::MIN, f32::max).ceil() as i32;
+        let mut crossings = Vec::new();
+        for y in min_y..max_y {
+            let cy = y as f32 + 0.5;
+            crossings.clear();
+            for (i, a) in points.iter().enumerate() {
+                let b = points[(i + 1) % points.len()];
+                if (a.1 <= cy && b.1 > cy) || (b.1 <= cy && a.1 > cy) {
+                    crossings.push(a.0 + (cy - a.1) / (b.1 - a.1) * (b.0 - a.0));
+                }
+            }
+            crossings.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            for pair in crossings.chunks_exact(2) {
+                for x in (pair[0] - 0.5).ceil() as i32..(pair[1] - 0.5).ceil() as i32 {
+                    self.blend(x, y, color, 1f32);
+                }
+            }
+        }
+    }
+
+    /// Samples the nearest image pixel for every pixel of the rect
+    fn draw_image_part(&mut self, rect: Rect<i32>, source: Rect<i32>, image: &Rc<Image>) {
+        if rect.width() <= 0 || rect.height() <= 0 || source.width() <= 0 || source.height() <= 0 {
+            return;
+        }
+        let scale_x = source.width() as f32 / rect.width() as f32;
+        let scale_y = source.height() as f32 / rect.height() as f32;
+        for y in rect.min.y..rect.max.y {
+            let sy = source.min.y + ((y - rect.min.y) as f32 * scale_y + scale_y / 2f32) as i32;
+            for x in rect.min.x..rect.max.x {
+                let sx = source.min.x + ((x - rect.min.x) as f32 * scale_x + scale_x / 2f32) as i32;
+                if sx < 0 || sy < 0 || sx >= image.width() as i32 || sy >= image.height() as i32 {
+                    continue;
+                }
+                let p = image.get_pixel(sx as u32, sy as u32);
+                let color = [p[0] as f32 / 255f32, p[1] as f32 / 255f32, p[2] as f32 / 255f32, p[3] as f32 / 255f32];
+                self.blend(x, y, color, 1f32);
+            }
+        }
+    }
+
+    /// Draws the text block laid out by `assets::layout_text()` with its real glyphs.
+    /// Other texts are drawn as boxes of glyph bounds, as their letters are unknown.
+    fn draw_text(&mut self, position: (f32, f32), color: u32, text: &Rc<FormattedTextBlock>) {
+        let (x, y) = position;
+        let color = to_floats(color);
+        let source = get_text_source(text);
+        let font = match &source {
+            None => None,
+            Some(s) => get_glyph_font(&s.font_name, &s.font_style)
+        };
+        for line in text.iter_lines() {
+            let baseline = y + line.baseline_position() + line.ascent();
+            for glyph in line.iter_glyphs() {
+                let letter = match (&source, &font) {
+                    (Some(source), Some(font)) => source.chars.get(glyph.user_index() as usize).map(|c| (source.size, font, *c)),
+                    _ => None
+                };
+                match letter {
+                    Some((size, font, c)) => {
+                        let position = rusttype::point(x + glyph.position_x(), baseline);
+                        let glyph = font.glyph(c).scaled(rusttype::Scale::uniform(size)).positioned(position);
+                        if let Some(bounds) = glyph.pixel_bounding_box() {
+                            glyph.draw(|gx, gy, coverage| {
+                                self.blend(bounds.min.x + gx as i32, bounds.min.y + gy as i32, color, coverage);
+                            });
+                        }
+                    }
+                    None => {
+                        if let Some(bounds) = glyph.pixel_bounding_box() {
+                            let (x1, y1) = (x + bounds.top_left().x, baseline + bounds.top_left().y);
+                            let (x2, y2) = (x + bounds.bottom_right().x, baseline + bounds.bottom_right().y);
+                            self.fill_area(x1, y1, x2, y2, [color[0], color[1], color[2], color[3] / 2f32]);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn clip_stack(&mut self) -> &mut ClipStack {
+        &mut self.clips
+    }
+
+    fn apply_clip(&mut self, rect: Option<Rect<i32>>) {
+        self.clip = rect;
+    }
+}
+
+/// Converts 0xAARRGGBB to [r, g, b, a] in range of 0..1
+fn to_floats(color: u32) -> [f32; 4] {
+    let channel = |shift: u32| ((color >> shift) & 0xff) as f32 / 255f32;
+    [channel(16), channel(8), channel(0), channel(24)]
+}
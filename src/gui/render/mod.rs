@@ -0,0 +1,227 @@
+mod canvas;
+mod speedy;
+
+use std::cmp::{max, min};
+use std::f32::consts::FRAC_PI_2;
+use std::rc::Rc;
+use speedy2d::font::FormattedTextBlock;
+pub use super::render::canvas::Canvas;
+pub use super::render::speedy::SpeedyRenderer;
+use gui::styles::selector::ImageMode;
+use gui::types::{rect, Rect};
+use gui::types;
+use gui::views::Borders;
+
+/// Low-level drawing primitives, that themes are painted with.
+/// Colors are given as 0xAARRGGBB, coordinates are in pixels.
+pub trait Renderer {
+    /// Fills all the target with one color, ignoring the clip
+    fn clear_screen(&mut self, color: u32);
+    fn fill_rect(&mut self, rect: Rect<i32>, color: u32);
+    /// Fills the rect with colors of its corners, clockwise from the top left one, mixed between them
+    fn fill_gradient(&mut self, rect: Rect<i32>, colors: [u32; 4]);
+    fn draw_line(&mut self, start: (f32, f32), end: (f32, f32), thickness: f32, color: u32);
+    /// Fills the polygon, given by its vertices in any order of traversal
+    fn fill_polygon(&mut self, points: &[(f32, f32)], color: u32);
+    /// Draws the `source` part of the image (in image pixels), stretched to the `rect`
+    fn draw_image_part(&mut self, rect: Rect<i32>, source: Rect<i32>, image: &Rc<Image>);
+    fn draw_text(&mut self, position: (f32, f32), color: u32, text: &Rc<FormattedTextBlock>);
+    fn clip_stack(&mut self) -> &mut ClipStack;
+    /// Applies the clip to the underlying surface, `None` means no clip at all
+    fn apply_clip(&mut self, rect: Option<Rect<i32>>);
+
+    /// Draws the border of given thickness inside of the rect
+    fn stroke_rect(&mut self, rect: Rect<i32>, thickness: f32, color: u32) {
+        let (x1, y1, x2, y2) = (rect.min.x as f32, rect.min.y as f32, rect.max.x as f32, rect.max.y as f32);
+        let half = thickness / 2f32;
+        self.draw_line((x1, y1 + half), (x2, y1 + half), thickness, color);
+        self.draw_line((x1, y2 - half), (x2, y2 - half), thickness, color);
+        self.draw_line((x1 + half, y1 + thickness), (x1 + half, y2 - thickness), thickness, color);
+        self.draw_line((x2 - half, y1 + thickness), (x2 - half, y2 - thickness), thickness, color);
+    }
+
+    fn fill_rounded_rect(&mut self, rect: Rect<i32>, radius: f32, color: u32) {
+        if radius <= 0f32 {
+            self.fill_rect(rect, color);
+            return;
+        }
+        let points = rounded_rect_points(rect, radius, 0f32);
+        self.fill_polygon(&points, color);
+    }
+
+    /// Draws the border of given thickness inside of the rect with rounded corners
+    fn stroke_rounded_rect(&mut self, rect: Rect<i32>, radius: f32, thickness: f32, color: u32) {
+        if radius <= 0f32 {
+            self.stroke_rect(rect, thickness, color);
+            return;
+        }
+        let points = rounded_rect_points(rect, radius, thickness / 2f32);
+        for (i, start) in points.iter().enumerate() {
+            let end = points[(i + 1) % points.len()];
+            self.draw_line(*start, end, thickness, color);
+        }
+    }
+
+    fn draw_image(&mut self, rect: Rect<i32>, image: &Rc<Image>) {
+        let source = types::rect((0, 0), (image.width() as i32, image.height() as i32));
+        self.draw_image_part(rect, source, image);
+    }
+
+    /// Repeats the image in its own size from the top left corner of the rect, cutting the last ones
+    fn draw_image_tiled(&mut self, rect: Rect<i32>, image: &Rc<Image>) {
+        let (width, height) = (image.width() as i32, image.height() as i32);
+        if width <= 0 || height <= 0 {
+            return;
+        }
+        let mut y = rect.min.y;
+        while y < rect.max.y {
+            let h = min(height, rect.max.y - y);
+            let mut x = rect.min.x;
+            while x < rect.max.x {
+                let w = min(width, rect.max.x - x);
+                self.draw_image_part(types::rect((x, y), (x + w, y + h)), types::rect((0, 0), (w, h)), image);
+                x += width;
+            }
+            y += height;
+        }
+    }
+
+    /// Draws the image cut by `insets` to nine parts: corners stay as they are,
+    /// edges are stretched along the rect, and the center is stretched in both directions.
+    fn draw_nine_patch(&mut self, rect: Rect<i32>, image: &Rc<Image>, insets: Borders) {
+        let (width, height) = (image.width() as i32, image.height() as i32);
+        let source_x = [0, insets.left, width - insets.right, width];
+        let source_y = [0, insets.top, height - insets.bottom, height];
+        let target_x = [rect.min.x, rect.min.x + insets.left, rect.max.x - insets.right, rect.max.x];
+        let target_y = [rect.min.y, rect.min.y + insets.top, rect.max.y - insets.bottom, rect.max.y];
+        for row in 0..3 {
+            for column in 0..3 {
+                let source = types::rect((source_x[column], source_y[row]), (source_x[column + 1], source_y[row + 1]));
+                let target = types::rect((target_x[column], target_y[row]), (target_x[column + 1], target_y[row + 1]));
+                if source.width() > 0 && source.height() > 0 && target.width() > 0 && target.height() > 0 {
+                    self.draw_image_part(target, source, image);
+                }
+            }
+        }
+    }
+
+    /// Draws the image in the rect as the mode tells
+    fn draw_image_mode(&mut self, rect: Rect<i32>, image: &Rc<Image>, mode: &ImageMode) {
+        match mode {
+            ImageMode::Stretch => self.draw_image(rect, image),
+            ImageMode::Tile => self.draw_image_tiled(rect, image),
+            ImageMode::NinePatch(insets) => self.draw_nine_patch(rect, image, *insets)
+        }
+    }
+
+    fn get_clip(&mut self) -> Rect<i32> {
+        self.clip_stack().current
+    }
+
+    fn set_clip(&mut self, rect: Rect<i32>) {
+        self.clip_stack().current = rect;
+        self.apply_clip(Some(rect));
+    }
+
+    /// Intersects current clip with the rect, and returns the result
+    fn clip_rect(&mut self, rect: Rect<i32>) -> Rect<i32> {
+        let current = self.get_clip();
+        let min_x = max(rect.min.x, current.min.x);
+        let max_x = min(rect.max.x, current.max.x);
+        let min_y = max(rect.min.y, current.min.y);
+        let max_y = min(rect.max.y, current.max.y);
+        let rect = types::rect((min_x, min_y), (max_x, max_y));
+        self.set_clip(rect);
+        rect
+    }
+
+    fn push_clip(&mut self) {
+        let stack = self.clip_stack();
+        stack.stack.push(stack.current);
+    }
+
+    fn pop_clip(&mut self) {
+        if let Some(clip) = self.clip_stack().stack.pop() {
+            self.set_clip(clip);
+        }
+    }
+}
+
+/// Current clip rect and the saved ones, that every renderer keeps.
+pub struct ClipStack {
+    current: Rect<i32>,
+    stack: Vec<Rect<i32>>
+}
+
+impl ClipStack {
+    pub fn new(width: i32, height: i32) -> Self {
+        ClipStack { current: rect((0, 0), (width, height)), stack: Vec::new() }
+    }
+
+    pub fn current(&self) -> Rect<i32> {
+        self.current
+    }
+}
+
+/// Decoded image with 4 bytes per pixel in RGBA order, that can be drawn by any renderer.
+pub struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>
+}
+
+#[allow(dead_code)]
+impl Image {
+    pub fn new(width: u32, height: u32, pixels: Vec<u8>) -> Self {
+        assert_eq!(pixels.len(), (width * height * 4) as usize, "Wrong size of image data");
+        Image { width, height, pixels }
+    }
+
+    /// Decodes PNG, JPEG or any other format that `image` crate knows
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        let image = image::load_from_memory(bytes).ok()?.to_rgba8();
+        let (width, height) = image.dimensions();
+        Some(Image { width, height, pixels: image.into_raw() })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Gets the pixel as [r, g, b, a]
+    pub fn get_pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let offset = ((y * self.width + x) * 4) as usize;
+        [self.pixels[offset], self.pixels[offset + 1], self.pixels[offset + 2], self.pixels[offset + 3]]
+    }
+}
+
+/// Makes the outline of rounded rect, going clockwise from the top left corner.
+/// The outline is moved inside of the rect by `inset` pixels.
+fn rounded_rect_points(rect: Rect<i32>, radius: f32, inset: f32) -> Vec<(f32, f32)> {
+    let (x1, y1) = (rect.min.x as f32 + inset, rect.min.y as f32 + inset);
+    let (x2, y2) = (rect.max.x as f32 - inset, rect.max.y as f32 - inset);
+    let radius = (radius - inset).max(0f32).min((x2 - x1) / 2f32).min((y2 - y1) / 2f32);
+    let steps = ((radius * FRAC_PI_2) / 2f32).ceil().clamp(1f32, 32f32) as i32;
+    let corners = [
+        (x1 + radius, y1 + radius, 2f32),
+        (x2 - radius, y1 + radius, 3f32),
+        (x2 - radius, y2 - radius, 0f32),
+        (x1 + radius, y2 - radius, 1f32)
+    ];
+    let mut points = Vec::with_capacity(corners.len() * (steps as usize + 1));
+    for (cx, cy, quarter) in corners.iter() {
+        for step in 0..=steps {
+            let angle = (quarter + step as f32 / steps as f32) * FRAC_PI_2;
+            points.push((cx + radius * angle.cos(), cy + radius * angle.sin()));
+        }
+    }
+    points
+}
@@ -0,0 +1,111 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::{Rc, Weak};
+use speedy2d::color::Color;
+use speedy2d::dimen::Vector2;
+use speedy2d::font::FormattedTextBlock;
+use speedy2d::Graphics2D;
+use speedy2d::image::{ImageDataType, ImageHandle, ImageSmoothingMode};
+use speedy2d::shape::{Polygon, Rectangle};
+use gui::render::{ClipStack, Image, Renderer};
+use gui::types::Rect;
+
+thread_local! {
+    /// Textures of images, that were already uploaded to GPU, by the address of image
+    static TEXTURES: RefCell<HashMap<usize, (Weak<Image>, ImageHandle)>> = RefCell::new(HashMap::new());
+}
+
+/// Renderer that draws on the window with `speedy2d` graphics.
+pub struct SpeedyRenderer<'g> {
+    graphics: &'g mut Graphics2D,
+    clips: ClipStack
+}
+
+impl<'g> SpeedyRenderer<'g> {
+    pub fn new(graphics: &'g mut Graphics2D, width: i32, height: i32) -> Self {
+        SpeedyRenderer { graphics, clips: ClipStack::new(width, height) }
+    }
+
+    fn get_texture(&mut self, image: &Rc<Image>) -> Option<ImageHandle> {
+        let key = Rc::as_ptr(image) as usize;
+        let cached = TEXTURES.with(|t| {
+            match t.borrow().get(&key) {
+                Some((weak, handle)) if weak.upgrade().map(|i| Rc::ptr_eq(&i, image)).unwrap_or(false) => Some(handle.clone()),
+                _ => None
+            }
+        });
+        if cached.is_some() {
+            return cached;
+        }
+        let size = (image.width(), image.height());
+        let handle = self.graphics.create_image_from_raw_pixels(ImageDataType::RGBA, ImageSmoothingMode::Linear, size, image.pixels()).ok()?;
+        TEXTURES.with(|t| {
+            let mut textures = t.borrow_mut();
+            textures.retain(|_, (weak, _)| weak.strong_count() > 0);
+            textures.insert(key, (Rc::downgrade(image), handle.clone()));
+        });
+        Some(handle)
+    }
+}
+
+impl<'g> Renderer for SpeedyRenderer<'g> {
+    fn clear_screen(&mut self, color: u32) {
+        self.graphics.set_clip(None);
+        self.graphics.clear_screen(Color::from_hex_argb(color));
+        let clip = self.clips.current();
+        self.apply_clip(Some(clip));
+    }
+
+    fn fill_rect(&mut self, rect: Rect<i32>, color: u32) {
+        self.graphics.draw_rectangle(to_rectangle(rect), Color::from_hex_argb(color));
+    }
+
+    fn fill_gradient(&mut self, rect: Rect<i32>, colors: [u32; 4]) {
+        let (x1, y1, x2, y2) = (rect.min.x as f32, rect.min.y as f32, rect.max.x as f32, rect.max.y as f32);
+        let positions = [Vector2::new(x1, y1), Vector2::new(x2, y1), Vector2::new(x2, y2), Vector2::new(x1, y2)];
+        self.graphics.draw_quad_four_color(positions, colors.map(Color::from_hex_argb));
+    }
+
+    fn draw_line(&mut self, start: (f32, f32), end: (f32, f32), thickness: f32, color: u32) {
+        self.graphics.draw_line(start, end, thickness, Color::from_hex_argb(color));
+    }
+
+    fn fill_polygon(&mut self, points: &[(f32, f32)], color: u32) {
+        if points.len() < 3 {
+            return;
+        }
+        let polygon = Polygon::new(points);
+        self.graphics.draw_polygon(&polygon, (0f32, 0f32), Color::from_hex_argb(color));
+    }
+
+    fn draw_image_part(&mut self, rect: Rect<i32>, source: Rect<i32>, image: &Rc<Image>) {
+        if image.width() == 0 || image.height() == 0 {
+            return;
+        }
+        if let Some(handle) = self.get_texture(image) {
+            let (w, h) = (image.width() as f32, image.height() as f32);
+            let coords = Rectangle::new(
+                Vector2::new(source.min.x as f32 / w, source.min.y as f32 / h),
+                Vector2::new(source.max.x as f32 / w, source.max.y as f32 / h)
+            );
+            self.graphics.draw_rectangle_image_subset_tinted(to_rectangle(rect), Color::WHITE, coords, &handle);
+        }
+    }
+
+    fn draw_text(&mut self, position: (f32, f32), color: u32, text: &Rc<FormattedTextBlock>) {
+        self.graphics.draw_text(position, Color::from_hex_argb(color), text);
+    }
+
+    fn clip_stack(&mut self) -> &mut ClipStack {
+        &mut self.clips
+    }
+
+    fn apply_clip(&mut self, rect: Option<Rect<i32>>) {
+        let rect = rect.map(|r| Rectangle::from_tuples((r.min.x, r.min.y), (r.max.x, r.max.y)));
+        self.graphics.set_clip(rect);
+    }
+}
+
+fn to_rectangle(rect: Rect<i32>) -> Rectangle {
+    Rectangle::new(Vector2::new(rect.min.x as f32, rect.min.y as f32), Vector2::new(rect.max.x as f32, rect.max.y as f32))
+}
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use quick_xml::escape::{escape, unescape};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use gui::assets::get_file;
+use gui::common::get_attributes;
+use gui::styles::parse_color;
+use gui::ui::{LoadError, Position};
+use gui::views::{Dimension, parse_value};
+
+/// Named values, that attributes reference like `text="@string/ok"`, `margin="@dimen/gap"`
+/// or `background="@color/accent"`. They are given in layout or loaded from a separate file:
+/// ```xml
+/// <Resources>
+///     <String name="ok" value="OK"/>
+///     <Dimen name="gap" value="8"/>
+///     <Color name="accent" value="#4a90d9"/>
+/// </Resources>
+/// ```
+/// Values that really start with `@` are written as `\@`.
+#[derive(Clone, Debug, Default)]
+pub struct Resources {
+    strings: HashMap<String, String>,
+    dimens: HashMap<String, String>,
+    colors: HashMap<String, String>
+}
+
+#[allow(dead_code)]
+impl Resources {
+    pub fn new() -> Self {
+        Resources::default()
+    }
+
+    /// Loads resources with the assets provider, see `from_xml()`
+    pub fn load(path: &str, warnings: &mut Vec<LoadError>) -> Result<Resources, LoadError> {
+        let file_error = |message: String| LoadError::File { path: path.to_owned(), message };
+        let bytes = get_file(path).ok_or_else(|| file_error(String::from("Resources are not found")))?;
+        let xml = String::from_utf8(bytes).map_err(|e| file_error(format!("Resources are not UTF-8: {}", e)))?;
+        Resources::from_xml(&xml, warnings)
+    }
+
+    /// Parses resources, the error tells where the XML is wrong.
+    /// Wrong values are skipped, and added to `warnings`.
+    pub fn from_xml(xml: &str, warnings: &mut Vec<LoadError>) -> Result<Resources, LoadError> {
+        let mut resources = Resources::new();
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true);
+        loop {
+            let e = match reader.read_event() {
+                Ok(Event::Start(e)) | Ok(Event::Empty(e)) => e,
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(LoadError::Syntax { position: Position::new(xml, reader.buffer_position()), message: e.to_string() }),
+                _ => continue
+            };
+            let position = Position::of_tag(xml, reader.buffer_position());
+            let tag = String::from_utf8_lossy(e.name().as_ref()).to_string();
+            let kind = match tag.as_str() {
+                "Resources" => continue,
+                "String" => "string",
+                "Dimen" => "dimen",
+                "Color" => "color",
+                _ => {
+                    warnings.push(LoadError::UnknownType { position, tag });
+                    continue;
+                }
+            };
+            let attributes = get_attributes(&e);
+            let name = attributes.iter().find(|(n, _)| n == "name").map(|(_, v)| v);
+            let value = attributes.iter().find(|(n, _)| n == "value").map(|(_, v)| v);
+            let (attribute, message) = match (name, value.map(|v| unescape(v))) {
+                (Some(name), Some(Ok(value))) => match resources.add(kind, name, &value) {
+                    Ok(_) => continue,
+                    Err(message) => ("value", format!("Resource @{}/{}: {}", kind, name, message))
+                },
+                (_, Some(Err(e))) => ("value", e.to_string()),
+                (None, _) => ("name", String::from("Resource must have name and value")),
+                (_, None) => ("value", String::from("Resource must have name and value"))
+            };
+            warnings.push(LoadError::Attribute { position, tag, attribute: attribute.to_owned(), message });
+        }
+        Ok(resources)
+    }
+
+    /// Adds the value of `string`, `dimen` or `color` kind, checking that it suits this kind
+    pub fn add(&mut self, kind: &str, name: &str, value: &str) -> Result<(), String> {
+        match kind {
+            "string" => {}
+            "dimen" => { parse_value::<Dimension>(value)?; }
+            "color" => {
+                if parse_color(value).is_none() {
+                    return Err(format!("Wrong color {:?}", value));
+                }
+            }
+            _ => return Err(format!("Unknown kind of resource {}", kind))
+        }
+        self.get_table_mut(kind).unwrap().insert(name.to_owned(), value.to_owned());
+        Ok(())
+    }
+
+    pub fn get(&self, kind: &str, name: &str) -> Option<&str> {
+        self.get_table(kind)?.get(name).map(|v| v.as_str())
+    }
+
+    /// Adds all values of other resources, replacing own values with the same names
+    pub fn append(&mut self, other: Resources) {
+        self.strings.extend(other.strings);
+        self.dimens.extend(other.dimens);
+        self.colors.extend(other.colors);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty() && self.dimens.is_empty() && self.colors.is_empty()
+    }
+
+    /// Gets the value, that the attribute references, or the value itself if it is not a reference
+    pub fn resolve(&self, value: &str) -> Result<String, String> {
+        if let Some(literal) = value.strip_prefix('\\') {
+            if literal.starts_with('@') {
+                return Ok(literal.to_owned());
+            }
+        }
+        let reference = match value.strip_prefix('@') {
+            None => return Ok(value.to_owned()),
+            Some(reference) => reference
+        };
+        let (kind, name) = reference.split_once('/')
+            .ok_or_else(|| format!("Wrong reference {:?}, expected @kind/name", value))?;
+        if self.get_table(kind).is_none() {
+            return Err(format!("Unknown kind of resource {}, expected string, dimen or color", kind));
+        }
+        match self.get(kind, name) {
+            None => Err(format!("Resource {} is not found", value)),
+            Some(value) => Ok(value.to_owned())
+        }
+    }
+
+    /// Writes all values as `<Resources>` element, that `from_xml()` can read, sorted by names
+    pub fn to_xml(&self, locale: Option<&str>) -> String {
+        let mut result = match locale {
+            None => String::from("<Resources>\n"),
+            Some(locale) => format!("<Resources locale=\"{}\">\n", escape(locale))
+        };
+        for (tag, table) in [("String", &self.strings), ("Dimen", &self.dimens), ("Color", &self.colors)] {
+            let mut names = table.keys().collect::<Vec<_>>();
+            names.sort_unstable();
+            for name in names {
+                result.push_str(&format!("    <{} name=\"{}\" value=\"{}\"/>\n", tag, escape(name), escape(&table[name])));
+            }
+        }
+        result.push_str("</Resources>\n");
+        result
+    }
+
+    fn get_table(&self, kind: &str) -> Option<&HashMap<String, String>> {
+        match kind {
+            "string" => Some(&self.strings),
+            "dimen" => Some(&self.dimens),
+            "color" => Some(&self.colors),
+            _ => None
+        }
+    }
+
+    fn get_table_mut(&mut self, kind: &str) -> Option<&mut HashMap<String, String>> {
+        match kind {
+            "string" => Some(&mut self.strings),
+            "dimen" => Some(&mut self.dimens),
+            "color" => Some(&mut self.colors),
+            _ => None
+        }
+    }
+}
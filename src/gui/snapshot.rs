@@ -0,0 +1,191 @@
+use std::fmt;
+use std::path::Path;
+use image::{Rgba, RgbaImage};
+use gui::render::Canvas;
+use gui::themes::{Classic, Theme, Typeface};
+use gui::ui::{LoadError, UI};
+
+/// Environment variable, that makes `Snapshot::compare()` overwrite golden images instead of comparing.
+pub const UPDATE_SNAPSHOTS_VAR: &str = "VINX_UPDATE_SNAPSHOTS";
+
+/// Renders XML layout offscreen and compares the result with a stored golden PNG image.
+///
+/// Fonts are loaded through the assets provider, so it has to be set before rendering.
+/// When the golden image is missing it is written from the current render, and the comparison fails,
+/// so that new images are always looked at. Set `VINX_UPDATE_SNAPSHOTS=1` to refresh all of them.
+pub struct Snapshot {
+    xml: String,
+    width: u32,
+    height: u32,
+    scale: f64,
+    tolerance: u8,
+    typeface: Typeface,
+    theme: String
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// The layout could not be loaded
+    Layout(LoadError),
+    /// There is no theme with this name
+    Theme(String),
+    /// There was no golden image, so it was created from current render
+    Created(String),
+    /// Golden image has another size
+    Size { expected: (u32, u32), actual: (u32, u32) },
+    /// Some pixels are different more than tolerance allows, the diff image is written next to the golden one
+    Mismatch { pixels: usize, diff: String },
+    Io(String)
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Layout(e) => write!(f, "Error loading layout: {}", e),
+            SnapshotError::Theme(name) => write!(f, "Unknown theme {}", name),
+            SnapshotError::Created(path) => write!(f, "Golden image {} was missing and has been created", path),
+            SnapshotError::Size { expected, actual } => {
+                write!(f, "Expected image of {}x{}, got {}x{}", expected.0, expected.1, actual.0, actual.1)
+            }
+            SnapshotError::Mismatch { pixels, diff } => write!(f, "{} pixels are different, see {}", pixels, diff),
+            SnapshotError::Io(e) => write!(f, "{}", e)
+        }
+    }
+}
+
+impl Snapshot {
+    pub fn new(xml: &str) -> Self {
+        Snapshot {
+            xml: xml.to_owned(),
+            width: 800,
+            height: 600,
+            scale: 1.0,
+            tolerance: 0,
+            typeface: Classic::typeface(),
+            theme: String::from("Classic")
+        }
+    }
+
+    pub fn with_size(mut self, width: u32, height: u32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: f64) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Sets maximum difference of every color channel, that is not counted as a change
+    pub fn with_tolerance(mut self, tolerance: u8) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    pub fn with_typeface(mut self, typeface: Typeface) -> Self {
+        self.typeface = typeface;
+        self
+    }
+
+    /// Sets the name of theme to paint with, as registered in `UI`
+    pub fn with_theme(mut self, theme: &str) -> Self {
+        self.theme = theme.to_owned();
+        self
+    }
+
+    /// Loads, lays out and paints the layout into new canvas
+    pub fn render(&self) -> Result<Canvas, SnapshotError> {
+        let mut ui = UI::from_xml(&self.xml, self.width, self.height, self.typeface.clone()).map_err(SnapshotError::Layout)?;
+        if !ui.set_theme(&self.theme) {
+            return Err(SnapshotError::Theme(self.theme.clone()));
+        }
+        ui.layout(self.width, self.height, self.scale);
+        let mut canvas = Canvas::new(self.width, self.height);
+        {
+            let mut theme = ui.create_theme(&mut canvas, self.width as i32, self.height as i32, self.scale);
+            ui.paint(theme.as_mut());
+        }
+        Ok(canvas)
+    }
+
+    /// Renders the layout and compares it with the golden image at given path.
+    /// On mismatch the image with different pixels marked red is saved as `<name>.diff.png`,
+    /// and the render itself as `<name>.actual.png`.
+    pub fn compare(&self, golden: &str) -> Result<(), SnapshotError> {
+        let actual = self.render()?.to_image();
+        let update = std::env::var(UPDATE_SNAPSHOTS_VAR).map(|v| !v.is_empty() && v != "0").unwrap_or(false);
+        if update || !Path::new(golden).exists() {
+            actual.save(golden).map_err(|e| SnapshotError::Io(e.to_string()))?;
+            return match update {
+                true => Ok(()),
+                false => Err(SnapshotError::Created(golden.to_owned()))
+            };
+        }
+
+        let expected = image::open(golden).map_err(|e| SnapshotError::Io(e.to_string()))?.to_rgba8();
+        if expected.dimensions() != actual.dimensions() {
+            return Err(SnapshotError::Size { expected: expected.dimensions(), actual: actual.dimensions() });
+        }
+
+        let (diff, pixels) = diff_images(&expected, &actual, self.tolerance);
+        if pixels == 0 {
+            return Ok(());
+        }
+        let base = golden.strip_suffix(".png").unwrap_or(golden);
+        let diff_path = format!("{}.diff.png", base);
+        diff.save(&diff_path).map_err(|e| SnapshotError::Io(e.to_string()))?;
+        actual.save(format!("{}.actual.png", base)).map_err(|e| SnapshotError::Io(e.to_string()))?;
+        Err(SnapshotError::Mismatch { pixels, diff: diff_path })
+    }
+}
+
+/// Makes an image where different pixels are red, and the same are a pale copy of expected image.
+/// Returns it with the count of different pixels.
+fn diff_images(expected: &RgbaImage, actual: &RgbaImage, tolerance: u8) -> (RgbaImage, usize) {
+    let mut diff = RgbaImage::new(expected.width(), expected.height());
+    let mut count = 0;
+    for (x, y, e) in expected.enumerate_pixels() {
+        let a = actual.get_pixel(x, y);
+        let different = e.0.iter().zip(a.0.iter()).any(|(e, a)| e.abs_diff(*a) > tolerance);
+        let pixel = if different {
+            count += 1;
+            Rgba([255, 0, 0, 255])
+        } else {
+            let gray = ((e[0] as u32 + e[1] as u32 + e[2] as u32) / 3) as u8;
+            let pale = 255 - (255 - gray) / 4;
+            Rgba([pale, pale, pale, 255])
+        };
+        diff.put_pixel(x, y, pixel);
+    }
+    (diff, count)
+}
+
+#[cfg(test)]
+mod tests {
+    use gui::assets::set_example_assets;
+    use gui::snapshot::Snapshot;
+
+    fn golden(name: &str) -> String {
+        format!("{}/tests/snapshots/{}.png", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    #[test]
+    fn breaking_frame() {
+        set_example_assets();
+        let xml = r#"<Frame id="main" width="max" height="max" font="Noto Sans" direction="vertical" padding="4">
+            <Frame id="wrapping" width="220" padding="4" breaking="true">
+                <Button id="one" text="One"/>
+                <Button id="two" text="Two" margin="2"/>
+                <Button id="three" text="Three"/>
+                <Button id="four" text="Four" break="true"/>
+                <Button id="long" text="Long button text"/>
+                <Label id="label" text="Label"/>
+            </Frame>
+        </Frame>"#;
+        let snapshot = Snapshot::new(xml).with_size(240, 200).with_tolerance(2);
+        if let Err(e) = snapshot.compare(&golden("breaking_frame")) {
+            panic!("{}", e);
+        }
+    }
+}
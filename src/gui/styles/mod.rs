@@ -1 +1,20 @@
-pub mod selector;
\ No newline at end of file
+pub mod selector;
+pub mod stylesheet;
+
+pub use self::stylesheet::{Stylesheet, StyleRule, Target};
+
+/// Parses the color as `#rrggbb`, `#aarrggbb` or `transparent`
+pub fn parse_color(value: &str) -> Option<u32> {
+    if let Some(hex) = value.strip_prefix('#') {
+        let color = u32::from_str_radix(hex, 16).ok()?;
+        return match hex.len() {
+            6 => Some(0xff000000 | color),
+            8 => Some(color),
+            _ => None
+        };
+    }
+    if value == "transparent" {
+        return Some(0x00000000);
+    }
+    None
+}
@@ -1,38 +1,137 @@
-use std::collections::HashMap;
-use super::super::themes::ViewState;
+use super::super::themes::{FontStyle, ViewState};
+use super::super::views::Borders;
 
 #[allow(unused)]
+#[derive(Clone, Debug)]
 pub enum DrawState {
     Transparent,
     Color(u32),
-    Image(String),
+    /// Path of the image in assets, and how to fit it to the view
+    Image(String, ImageMode),
+    /// Colors of corners, clockwise from the top left one
     Gradient(u32, u32, u32, u32)
 }
 
+/// How the image fills the rect of view
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImageMode {
+    Stretch,
+    Tile,
+    /// Corners of the image of given sizes are kept, and the rest is stretched
+    NinePatch(Borders)
+}
+
 #[allow(unused)]
+#[derive(Clone, Debug)]
 pub enum Font {
-    Color(u32)
+    Color(u32),
+    Name(String),
+    Style(FontStyle)
+}
+
+/// Set of `ViewState` flags, that must have given values for the data to apply.
+/// Flags that are `None` match any state.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StateMatch {
+    pub enabled: Option<bool>,
+    pub focusable: Option<bool>,
+    pub focused: Option<bool>,
+    pub hovered: Option<bool>,
+    pub pressed: Option<bool>,
+    pub checked: Option<bool>
+}
+
+impl StateMatch {
+    /// Matches only this exact state
+    pub fn exact(state: &ViewState) -> Self {
+        StateMatch {
+            enabled: Some(state.enabled),
+            focusable: Some(state.focusable),
+            focused: Some(state.focused),
+            hovered: Some(state.hovered),
+            pressed: Some(state.pressed),
+            checked: Some(state.checked)
+        }
+    }
+
+    pub fn matches(&self, state: &ViewState) -> bool {
+        let check = |flag: &Option<bool>, value: bool| flag.map(|f| f == value).unwrap_or(true);
+        check(&self.enabled, state.enabled)
+            && check(&self.focusable, state.focusable)
+            && check(&self.focused, state.focused)
+            && check(&self.hovered, state.hovered)
+            && check(&self.pressed, state.pressed)
+            && check(&self.checked, state.checked)
+    }
+
+    /// Checks if any flag is set
+    pub fn is_conditional(&self) -> bool {
+        *self != StateMatch::default()
+    }
+
+    /// Sets the flag by its name, as it is written in XML. Returns false for unknown names,
+    /// and an error if the value is not `true` or `false`. `disabled` is the inverse of `enabled`.
+    pub fn set_flag(&mut self, name: &str, value: &str) -> Result<bool, String> {
+        if !matches!(name, "enabled" | "disabled" | "focusable" | "focused" | "hovered" | "pressed" | "checked") {
+            return Ok(false);
+        }
+        let value = match value.parse() {
+            Ok(value) => Some(value),
+            Err(_) => return Err(format!("Wrong state flag {:?}, must be true or false", value))
+        };
+        match name {
+            "enabled" => self.enabled = value,
+            "disabled" => self.enabled = value.map(|v: bool| !v),
+            "focusable" => self.focusable = value,
+            "focused" => self.focused = value,
+            "hovered" => self.hovered = value,
+            "pressed" => self.pressed = value,
+            "checked" => self.checked = value,
+            &_ => return Ok(false)
+        }
+        Ok(true)
+    }
 }
 
 /// A selector for element styles.
+/// The data is chosen by state of the element, the later added data wins.
 pub struct Selector<T> {
-    states: HashMap<ViewState, T>
+    states: Vec<(StateMatch, T)>
 }
 
 #[allow(unused)]
 impl<T> Selector<T> {
     pub fn new() -> Self {
-        Selector { states: HashMap::new() }
+        Selector { states: Vec::new() }
     }
 
+    /// Adds the data for this exact state
     pub fn add_state(&mut self, state: ViewState, data: T) {
-        self.states.insert(state, data);
+        self.states.push((StateMatch::exact(&state), data));
+    }
+
+    /// Adds the data for all states that match
+    pub fn add_match(&mut self, state: StateMatch, data: T) {
+        self.states.push((state, data));
     }
 
     pub fn get_state(&self, state: &ViewState) -> Option<&T> {
-        self.states.get(state)
+        self.states.iter().rev().find(|(m, _)| m.matches(state)).map(|(_, data)| data)
+    }
+
+    /// Gets all the data for this state, in order of adding
+    pub fn get_states<'a>(&'a self, state: &'a ViewState) -> impl Iterator<Item = &'a T> + 'a {
+        self.states.iter().filter(move |(m, _)| m.matches(state)).map(|(_, data)| data)
+    }
+
+    pub fn clear(&mut self) {
+        self.states.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
     }
 }
 
 pub type MainSelector = Selector<DrawState>;
-pub type FontSelector = Selector<Font>;
\ No newline at end of file
+pub type FontSelector = Selector<Font>;
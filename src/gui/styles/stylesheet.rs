@@ -0,0 +1,391 @@
+use std::fmt;
+use quick_xml::escape::escape;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use gui::assets::get_file;
+use gui::common::get_unescaped_attributes;
+use gui::styles::parse_color;
+use gui::styles::selector::{DrawState, Font, ImageMode, StateMatch};
+use gui::themes::FontStyle;
+use gui::ui::{LoadError, Position};
+use gui::views::{Borders, FieldsMain};
+
+/// Views that one rule targets, written like `Button`, `#ok`, `.primary`, `Button.primary` or `*`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Target {
+    pub view_type: Option<String>,
+    pub id: Option<String>,
+    pub classes: Vec<String>
+}
+
+impl Target {
+    pub fn parse(text: &str) -> Option<Target> {
+        let text = text.trim();
+        if text.is_empty() {
+            return None;
+        }
+        let mut target = Target::default();
+        let mut kind = ' ';
+        let mut name = String::new();
+        for c in text.chars().chain(std::iter::once('\0')) {
+            if c != '#' && c != '.' && c != '\0' {
+                name.push(c);
+                continue;
+            }
+            match kind {
+                '#' if !name.is_empty() => target.id = Some(name.clone()),
+                '.' if !name.is_empty() => target.classes.push(name.clone()),
+                ' ' if !name.is_empty() && name != "*" => target.view_type = Some(name.clone()),
+                ' ' => {}
+                _ => return None
+            }
+            kind = c;
+            name.clear();
+        }
+        Some(target)
+    }
+
+    pub fn matches(&self, view_type: &str, main: &FieldsMain) -> bool {
+        if let Some(t) = &self.view_type {
+            if t != view_type {
+                return false;
+            }
+        }
+        if let Some(id) = &self.id {
+            if id != &main.id {
+                return false;
+            }
+        }
+        self.classes.iter().all(|c| main.has_class(c))
+    }
+
+    /// Ids weigh more than classes, and classes more than types
+    pub fn specificity(&self) -> u32 {
+        let id = if self.id.is_some() { 100 } else { 0 };
+        let view_type = if self.view_type.is_some() { 1 } else { 0 };
+        id + self.classes.len() as u32 * 10 + view_type
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.view_type {
+            Some(view_type) => write!(f, "{}", view_type)?,
+            None if self.id.is_none() && self.classes.is_empty() => write!(f, "*")?,
+            None => {}
+        }
+        if let Some(id) = &self.id {
+            write!(f, "#{}", id)?;
+        }
+        for class in &self.classes {
+            write!(f, ".{}", class)?;
+        }
+        Ok(())
+    }
+}
+
+/// One `<Style>` of the stylesheet: properties for views, that match any of targets, in given state.
+#[derive(Clone, Debug, Default)]
+pub struct StyleRule {
+    pub targets: Vec<Target>,
+    pub state: StateMatch,
+    pub background: Option<DrawState>,
+    pub color: Option<DrawState>,
+    pub font: Option<String>,
+    pub font_style: Option<FontStyle>
+}
+
+impl StyleRule {
+    /// Parses the rule from attributes of `<Style>` element.
+    /// Wrong attributes are skipped, and added to `problems` with their messages.
+    pub fn parse(attributes: &[(String, String)], problems: &mut Vec<(String, String)>) -> Option<StyleRule> {
+        let mut rule = StyleRule::default();
+        let mut wrong_state = false;
+        let mut problem = |name: &str, message: String| problems.push((name.to_owned(), message));
+        for (name, value) in attributes {
+            match name.as_str() {
+                "select" => {
+                    for target in value.split(',') {
+                        match Target::parse(target) {
+                            None => problem(name, format!("Wrong target {:?}", target.trim())),
+                            Some(target) => rule.targets.push(target)
+                        }
+                    }
+                }
+                "background" | "color" => {
+                    let state = parse_draw_state(value);
+                    if state.is_none() {
+                        problem(name, format!("Wrong color or background {:?}", value));
+                    }
+                    match name.as_str() {
+                        "background" => rule.background = state,
+                        _ => rule.color = state
+                    }
+                }
+                "font" => rule.font = Some(value.clone()),
+                "font_style" => rule.font_style = Some(FontStyle::from(value.as_str())),
+                name => match rule.state.set_flag(name, value) {
+                    Ok(true) => {}
+                    Ok(false) => problem(name, String::from("Unknown style property")),
+                    Err(message) => {
+                        problem(name, message);
+                        wrong_state = true;
+                    }
+                }
+            }
+        }
+        if rule.targets.is_empty() {
+            problem("select", String::from("Style must have targets in select"));
+            return None;
+        }
+        if wrong_state {
+            // Without the flag the rule would match every state
+            return None;
+        }
+        Some(rule)
+    }
+
+    /// Writes the rule as `<Style>` element, that `parse()` can read
+    pub fn to_xml(&self) -> String {
+        let targets = self.targets.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(", ");
+        let mut result = format!("<Style select=\"{}\"", escape(&targets));
+        let flags = [
+            ("enabled", self.state.enabled),
+            ("focusable", self.state.focusable),
+            ("focused", self.state.focused),
+            ("hovered", self.state.hovered),
+            ("pressed", self.state.pressed),
+            ("checked", self.state.checked)
+        ];
+        for (name, value) in flags {
+            if let Some(value) = value {
+                result.push_str(&format!(" {}=\"{}\"", name, value));
+            }
+        }
+        let properties = [
+            ("background", self.background.as_ref().map(|b| b.to_string())),
+            ("color", self.color.as_ref().map(|c| c.to_string())),
+            ("font", self.font.clone()),
+            ("font_style", self.font_style.as_ref().map(|s| s.to_string()))
+        ];
+        for (name, value) in properties {
+            if let Some(value) = value {
+                result.push_str(&format!(" {}=\"{}\"", name, escape(&value)));
+            }
+        }
+        result.push_str("/>");
+        result
+    }
+
+    /// Gets specificity of the most specific target that matches the view, or `None` if none of them match
+    pub fn get_specificity(&self, view_type: &str, main: &FieldsMain) -> Option<u32> {
+        self.targets.iter()
+            .filter(|t| t.matches(view_type, main))
+            .map(|t| t.specificity())
+            .max()
+    }
+}
+
+/// Parses the background or text color, it can be a color, `transparent`, or one of these:
+/// * `gradient(#top, #bottom)` or `gradient(#left, #right, horizontal)`,
+/// * `gradient(#top_left, #top_right, #bottom_right, #bottom_left)`,
+/// * `image(path)` stretched to the view, `image(path, tile)`,
+/// * `image(path, nine-patch 8)` or `image(path, nine-patch 8 12 8 12)` with sizes of top, left, right and bottom edges.
+pub fn parse_draw_state(value: &str) -> Option<DrawState> {
+    let value = value.trim();
+    if value == "transparent" {
+        return Some(DrawState::Transparent);
+    }
+    if let Some(args) = get_function_args(value, "gradient") {
+        parse_gradient(&args)
+    } else if let Some(args) = get_function_args(value, "image") {
+        parse_image(&args)
+    } else {
+        parse_color(value).map(DrawState::Color)
+    }
+}
+
+/// Gets arguments of the value like `name(arg1, arg2)`
+fn get_function_args(value: &str, name: &str) -> Option<Vec<String>> {
+    let args = value.strip_prefix(name)?.trim_start().strip_prefix('(')?.strip_suffix(')')?;
+    Some(args.split(',').map(|a| a.trim().to_owned()).collect())
+}
+
+fn parse_gradient(args: &[String]) -> Option<DrawState> {
+    let args = args.iter().map(|a| a.as_str()).collect::<Vec<_>>();
+    match args.as_slice() {
+        [top, bottom] => {
+            let (top, bottom) = (parse_color(top)?, parse_color(bottom)?);
+            Some(DrawState::Gradient(top, top, bottom, bottom))
+        }
+        [left, right, "horizontal"] => {
+            let (left, right) = (parse_color(left)?, parse_color(right)?);
+            Some(DrawState::Gradient(left, right, right, left))
+        }
+        [top, bottom, "vertical"] => parse_gradient(&[top.to_string(), bottom.to_string()]),
+        [c1, c2, c3, c4] => Some(DrawState::Gradient(parse_color(c1)?, parse_color(c2)?, parse_color(c3)?, parse_color(c4)?)),
+        _ => None
+    }
+}
+
+fn parse_image(args: &[String]) -> Option<DrawState> {
+    let path = args.first().filter(|p| !p.is_empty())?.clone();
+    let mode = match args.get(1).map(|m| m.as_str()) {
+        None | Some("stretch") => ImageMode::Stretch,
+        Some("tile") => ImageMode::Tile,
+        Some(mode) => {
+            let sizes = mode.strip_prefix("nine-patch")?
+                .split_whitespace()
+                .map(|s| s.parse::<i32>().ok())
+                .collect::<Option<Vec<_>>>()?;
+            match sizes.as_slice() {
+                [all] => ImageMode::NinePatch(Borders::with_padding(*all)),
+                [top, left, right, bottom] => ImageMode::NinePatch(Borders::new(*top, *left, *right, *bottom)),
+                _ => return None
+            }
+        }
+    };
+    Some(DrawState::Image(path, mode))
+}
+
+/// Writes the background or text color in the form that `parse_draw_state()` reads
+impl fmt::Display for DrawState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DrawState::Transparent => write!(f, "transparent"),
+            DrawState::Color(color) => write!(f, "#{:08x}", color),
+            DrawState::Gradient(c1, c2, c3, c4) => write!(f, "gradient(#{:08x}, #{:08x}, #{:08x}, #{:08x})", c1, c2, c3, c4),
+            DrawState::Image(path, ImageMode::Stretch) => write!(f, "image({})", path),
+            DrawState::Image(path, ImageMode::Tile) => write!(f, "image({}, tile)", path),
+            DrawState::Image(path, ImageMode::NinePatch(b)) => {
+                write!(f, "image({}, nine-patch {} {} {} {})", path, b.top, b.left, b.right, b.bottom)
+            }
+        }
+    }
+}
+
+/// Set of style rules, that are given in layout or loaded from a separate file like this:
+/// ```xml
+/// <Stylesheet>
+///     <Style select="Button" background="#3a3d41" color="#e0e0e0"/>
+///     <Style select="#header" background="gradient(#4a90d9, #2a5a99)"/>
+///     <Style select="Button.primary, #ok" hovered="true" background="#4a90d9" font_style="Bold"/>
+///     <Style select="Edit" disabled="true" color="#7a7d82"/>
+/// </Stylesheet>
+/// ```
+/// When several rules set the same property, the one with more specific target wins,
+/// and for equally specific ones the later rule wins.
+#[derive(Clone, Default)]
+pub struct Stylesheet {
+    rules: Vec<StyleRule>
+}
+
+#[allow(dead_code)]
+impl Stylesheet {
+    pub fn new() -> Self {
+        Stylesheet { rules: Vec::new() }
+    }
+
+    /// Loads the stylesheet with the assets provider, see `from_xml()`
+    pub fn load(path: &str, warnings: &mut Vec<LoadError>) -> Result<Stylesheet, LoadError> {
+        let file_error = |message: String| LoadError::File { path: path.to_owned(), message };
+        let bytes = get_file(path).ok_or_else(|| file_error(String::from("Stylesheet is not found")))?;
+        let xml = String::from_utf8(bytes).map_err(|e| file_error(format!("Stylesheet is not UTF-8: {}", e)))?;
+        Stylesheet::from_xml(&xml, warnings)
+    }
+
+    /// Parses the stylesheet, the error tells where the XML is wrong.
+    /// Wrong rules and attributes are skipped, and added to `warnings`.
+    pub fn from_xml(xml: &str, warnings: &mut Vec<LoadError>) -> Result<Stylesheet, LoadError> {
+        let mut sheet = Stylesheet::new();
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true);
+        loop {
+            let e = match reader.read_event() {
+                Ok(Event::Start(e)) | Ok(Event::Empty(e)) => e,
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(LoadError::Syntax { position: Position::new(xml, reader.buffer_position()), message: e.to_string() }),
+                _ => continue
+            };
+            let position = Position::of_tag(xml, reader.buffer_position());
+            match e.name().as_ref() {
+                b"Stylesheet" => {}
+                b"Style" => {
+                    let mut problems = Vec::new();
+                    if let Some(rule) = StyleRule::parse(&get_unescaped_attributes(&e), &mut problems) {
+                        sheet.add_rule(rule);
+                    }
+                    for (attribute, message) in problems {
+                        warnings.push(LoadError::Attribute { position, tag: String::from("Style"), attribute, message });
+                    }
+                }
+                name => warnings.push(LoadError::UnknownType { position, tag: String::from_utf8_lossy(name).to_string() })
+            }
+        }
+        Ok(sheet)
+    }
+
+    pub fn add_rule(&mut self, rule: StyleRule) {
+        self.rules.push(rule);
+    }
+
+    /// Adds all rules of other stylesheet after own rules
+    pub fn append(&mut self, other: Stylesheet) {
+        self.rules.extend(other.rules);
+    }
+
+    pub fn get_rules(&self) -> &[StyleRule] {
+        &self.rules
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Writes all rules as `<Stylesheet>` element, that `from_xml()` can read
+    pub fn to_xml(&self) -> String {
+        let mut result = String::from("<Stylesheet>\n");
+        for rule in &self.rules {
+            result.push_str(&format!("    {}\n", rule.to_xml()));
+        }
+        result.push_str("</Stylesheet>\n");
+        result
+    }
+
+    pub fn clear(&mut self) {
+        self.rules.clear();
+    }
+
+    /// Checks if some fonts depend on state, so the views need layout when their state changes
+    pub fn has_state_fonts(&self) -> bool {
+        self.rules.iter().any(|r| r.state.is_conditional() && (r.font.is_some() || r.font_style.is_some()))
+    }
+
+    /// Fills background, foreground and font selectors of the view with matching rules.
+    /// Selectors are cleared before that, so that this can be done again after changes.
+    pub fn apply(&self, view_type: &str, main: &mut FieldsMain) {
+        let mut matched = self.rules.iter()
+            .enumerate()
+            .filter_map(|(order, rule)| rule.get_specificity(view_type, main).map(|s| (s, order, rule)))
+            .collect::<Vec<_>>();
+        matched.sort_by_key(|(specificity, order, _)| (*specificity, *order));
+
+        main.background.clear();
+        main.foreground.clear();
+        main.font.clear();
+        for (_, _, rule) in matched {
+            if let Some(background) = &rule.background {
+                main.background.add_match(rule.state.clone(), background.clone());
+            }
+            if let Some(color) = &rule.color {
+                main.foreground.add_match(rule.state.clone(), color.clone());
+            }
+            if let Some(font) = &rule.font {
+                main.font.add_match(rule.state.clone(), Font::Name(font.clone()));
+            }
+            if let Some(style) = &rule.font_style {
+                main.font.add_match(rule.state.clone(), Font::Style(style.clone()));
+            }
+        }
+    }
+}
@@ -1,26 +1,18 @@
-use std::cmp::{max, min};
-use std::collections::VecDeque;
 use std::rc::Rc;
-use speedy2d::color::Color;
 use speedy2d::dimen::Vector2;
 use speedy2d::font::FormattedTextBlock;
-use speedy2d::Graphics2D;
-use speedy2d::shape::Rectangle;
-use gui::styles::selector::{DrawState, MainSelector};
-use gui::themes::{Theme, Typeface, ViewState};
+use gui::render::{Image, Renderer};
+use gui::styles::selector::{DrawState, ImageMode, MainSelector};
+use gui::themes::{Theme, ThemeFactory, Typeface, ViewState};
 use gui::themes::utils::draw_dashed_rectangle;
 use gui::types::Rect;
-use gui::types;
-use gui::types::rect;
 
 #[allow(unused)]
 pub struct Classic<'h> {
-    graphics: &'h mut Graphics2D,
+    renderer: &'h mut dyn Renderer,
     width: i32,
     height: i32,
-    scale: f64,
-    current_clip: Rect<i32>,
-    clip_stack: VecDeque<Rect<i32>>
+    scale: f64
 }
 
 #[allow(dead_code)]
@@ -32,17 +24,27 @@ impl<'h> Classic<'h> {
     const DARK: u32 = 0xff404040;
     const BLACK: u32 = 0xff000000;
 
-    pub fn new(graphics: &'h mut Graphics2D, width: i32, height: i32, scale: f64) -> Self {
-        let current_clip = rect((0, 0), (width, height));
-        Classic { graphics, width, height, scale, current_clip, clip_stack: VecDeque::new() }
+    pub fn new(renderer: &'h mut dyn Renderer, width: i32, height: i32, scale: f64) -> Self {
+        Classic { renderer, width, height, scale }
+    }
+}
+
+/// Makes `Classic` themes for `UI`.
+pub struct ClassicFactory;
+
+impl ThemeFactory for ClassicFactory {
+    fn create<'r>(&self, renderer: &'r mut dyn Renderer, width: i32, height: i32, scale: f64) -> Box<dyn Theme + 'r> {
+        Box::new(Classic::new(renderer, width, height, scale))
+    }
+
+    fn typeface(&self) -> Typeface {
+        Classic::typeface()
     }
 }
 
 impl<'h> Theme for Classic<'h> {
     fn clear_screen(&mut self) {
-        self.graphics.set_clip(None);
-        self.graphics.clear_screen(Color::from_hex_rgb(Classic::BACKGROUND));
-        self.set_clip(self.current_clip);
+        self.renderer.clear_screen(Classic::BACKGROUND);
     }
 
     fn typeface() -> Typeface {
@@ -75,41 +77,29 @@ impl<'h> Theme for Classic<'h> {
     }
 
     fn set_clip(&mut self, rect: Rect<i32>) {
-        self.current_clip = rect;
-        let rect = Rectangle::from_tuples((rect.min.x, rect.min.y), (rect.max.x, rect.max.y));
-        self.graphics.set_clip(Some(rect));
+        self.renderer.set_clip(rect);
     }
 
     fn clip_rect(&mut self, rect: Rect<i32>) -> Rect<i32> {
-        let min_x = max(rect.min.x, self.current_clip.min.x);
-        let max_x = min(rect.max.x, self.current_clip.max.x);
-        let min_y = max(rect.min.y, self.current_clip.min.y);
-        let max_y = min(rect.max.y, self.current_clip.max.y);
-        let rect = types::rect((min_x, min_y), (max_x, max_y));
-        self.set_clip(rect);
-        rect
+        self.renderer.clip_rect(rect)
     }
 
     fn push_clip(&mut self) {
-        self.clip_stack.push_back(self.current_clip);
+        self.renderer.push_clip();
     }
 
     fn pop_clip(&mut self) {
-        if let Some(clip) = self.clip_stack.pop_back() {
-            self.set_clip(clip);
-        }
+        self.renderer.pop_clip();
     }
 
     #[allow(unused)]
     fn draw_button_back(&mut self, rect: Rect<i32>, state: ViewState) {
-        let top_left = Vector2::new(rect.min.x as f32, rect.min.y as f32);
-        let bottom_right = Vector2::new(rect.max.x as f32, rect.max.y as f32);
         let color = if state.hovered || state.pressed {
-            Color::from_hex_rgb(Classic::BACKGROUND_LIGHT)
+            Classic::BACKGROUND_LIGHT
         } else {
-            Color::from_hex_rgb(Classic::BACKGROUND)
+            Classic::BACKGROUND
         };
-        self.graphics.draw_rectangle(Rectangle::new(top_left, bottom_right), color);
+        self.renderer.fill_rect(rect, color);
     }
 
     #[allow(unused)]
@@ -121,49 +111,42 @@ impl<'h> Theme for Classic<'h> {
         match state.pressed && state.hovered {
             true => {
                 let border2: f32 = (self.scale * 2f64) as f32;
-                let color = Color::from_hex_rgb(Classic::LIGHT);
-                self.graphics.draw_line((top_left.x, top_left.y + border_half), (bottom_right.x - border, top_left.y + border_half), border, color);
-                self.graphics.draw_line((top_left.x + border_half, top_left.y), (top_left.x + border_half, bottom_right.y - border), border, color);
-                let color = Color::from_hex_rgb(Classic::DARK);
-                self.graphics.draw_line((top_left.x + border, top_left.y + border + border_half), (bottom_right.x - border, top_left.y + border + border_half), border, color);
-                self.graphics.draw_line((top_left.x + border + border_half, top_left.y + border), (top_left.x + border + border_half, bottom_right.y - border), border, color);
-
-                let color = Color::from_hex_rgb(0xffffff);
-                self.graphics.draw_line((top_left.x + border, bottom_right.y - border - border_half), (bottom_right.x - border, bottom_right.y - border - border_half), border, color);
-                self.graphics.draw_line((bottom_right.x - border - border_half, top_left.y + border), (bottom_right.x - border - border_half, bottom_right.y - border), border, color);
+                let color = Classic::LIGHT;
+                self.renderer.draw_line((top_left.x, top_left.y + border_half), (bottom_right.x - border, top_left.y + border_half), border, color);
+                self.renderer.draw_line((top_left.x + border_half, top_left.y), (top_left.x + border_half, bottom_right.y - border), border, color);
+                let color = Classic::DARK;
+                self.renderer.draw_line((top_left.x + border, top_left.y + border + border_half), (bottom_right.x - border, top_left.y + border + border_half), border, color);
+                self.renderer.draw_line((top_left.x + border + border_half, top_left.y + border), (top_left.x + border + border_half, bottom_right.y - border), border, color);
+
+                let color = 0xffffffff;
+                self.renderer.draw_line((top_left.x + border, bottom_right.y - border - border_half), (bottom_right.x - border, bottom_right.y - border - border_half), border, color);
+                self.renderer.draw_line((bottom_right.x - border - border_half, top_left.y + border), (bottom_right.x - border - border_half, bottom_right.y - border), border, color);
             }
             false => {
-                let color = Color::from_hex_rgb(0xffffff);
-                self.graphics.draw_line((top_left.x, top_left.y + border_half), (bottom_right.x - border_half, top_left.y + border_half), border, color);
-                self.graphics.draw_line((top_left.x + border_half, top_left.y + border_half), (top_left.x + border_half, bottom_right.y - border_half), border, color);
-                let color = Color::from_hex_rgb(Classic::DARK);
-                self.graphics.draw_line((top_left.x - border_half, bottom_right.y - border_half), (bottom_right.x, bottom_right.y - border_half), border, color);
-                self.graphics.draw_line((bottom_right.x - border_half, top_left.y - border_half), (bottom_right.x - border_half, bottom_right.y + 0.5), border, color);
-                let color = Color::from_hex_rgb(Classic::LIGHT);
-                self.graphics.draw_line((top_left.x + border, bottom_right.y - border - border_half), (bottom_right.x - border, bottom_right.y - border - border_half), border, color);
-                self.graphics.draw_line((bottom_right.x - border - border_half, top_left.y + border), (bottom_right.x - border - border_half, bottom_right.y - border), border, color);
+                let color = 0xffffffff;
+                self.renderer.draw_line((top_left.x, top_left.y + border_half), (bottom_right.x - border_half, top_left.y + border_half), border, color);
+                self.renderer.draw_line((top_left.x + border_half, top_left.y + border_half), (top_left.x + border_half, bottom_right.y - border_half), border, color);
+                let color = Classic::DARK;
+                self.renderer.draw_line((top_left.x - border_half, bottom_right.y - border_half), (bottom_right.x, bottom_right.y - border_half), border, color);
+                self.renderer.draw_line((bottom_right.x - border_half, top_left.y - border_half), (bottom_right.x - border_half, bottom_right.y + 0.5), border, color);
+                let color = Classic::LIGHT;
+                self.renderer.draw_line((top_left.x + border, bottom_right.y - border - border_half), (bottom_right.x - border, bottom_right.y - border - border_half), border, color);
+                self.renderer.draw_line((bottom_right.x - border - border_half, top_left.y + border), (bottom_right.x - border - border_half, bottom_right.y - border), border, color);
             }
         }
         if state.focused {
-            let color = Color::from_hex_rgb(0x000000);
+            let color = 0xff000000;
             let padding = border * 4f32;
-            draw_dashed_rectangle(self.graphics, top_left.x + padding - 1.0, top_left.y + padding - 1.0, bottom_right.x - padding, bottom_right.y - padding, 2.5f32, border, color);
-            //self.graphics.draw_line((top_left.x + border * 4f32, top_left.y + border * 4f32), (bottom_right.x - border * 4f32, top_left.y + border * 4f32), border, color);
-            //self.graphics.draw_line((top_left.x + border * 4f32, bottom_right.y - border * 4f32), (bottom_right.x - border * 4f32, bottom_right.y - border * 4f32), border, color);
+            draw_dashed_rectangle(self.renderer, top_left.x + padding - 1.0, top_left.y + padding - 1.0, bottom_right.x - padding, bottom_right.y - padding, 2.5f32, border, color);
+            //self.renderer.draw_line((top_left.x + border * 4f32, top_left.y + border * 4f32), (bottom_right.x - border * 4f32, top_left.y + border * 4f32), border, color);
+            //self.renderer.draw_line((top_left.x + border * 4f32, bottom_right.y - border * 4f32), (bottom_right.x - border * 4f32, bottom_right.y - border * 4f32), border, color);
         }
     }
 
-    #[allow(unused)]
-    fn draw_button_text(&mut self, rect: Rect<i32>, state: ViewState, size: usize, text: &str) {
-        todo!()
-    }
-
     #[allow(unused)]
     fn draw_edit_back(&mut self, rect: Rect<i32>, state: ViewState) {
-        let top_left = Vector2::new(rect.min.x as f32, rect.min.y as f32);
-        let bottom_right = Vector2::new(rect.max.x as f32, rect.max.y as f32);
-        let color = Color::from_hex_rgb(0xffffff);
-        self.graphics.draw_rectangle(Rectangle::new(top_left, bottom_right), color);
+        let color = 0xffffffff;
+        self.renderer.fill_rect(rect, color);
     }
 
     #[allow(unused)]
@@ -173,29 +156,27 @@ impl<'h> Theme for Classic<'h> {
         let border_half: f32 = (self.scale / 2f64) as f32;
         let top_left = Vector2::new(rect.min.x as f32, rect.min.y as f32);
         let bottom_right = Vector2::new(rect.max.x as f32, rect.max.y as f32);
-        let color = Color::from_hex_rgb(Classic::LIGHT);
-        self.graphics.draw_line((top_left.x, top_left.y + border_half), (bottom_right.x - border, top_left.y + border_half), border, color);
-        self.graphics.draw_line((top_left.x + border_half, top_left.y), (top_left.x + border_half, bottom_right.y - border), border, color);
-        let color = Color::from_hex_rgb(Classic::DARK);
-        self.graphics.draw_line((top_left.x + border, top_left.y + border + border_half), (bottom_right.x - border, top_left.y + border + border_half), border, color);
-        self.graphics.draw_line((top_left.x + border + border_half, top_left.y + border), (top_left.x + border + border_half, bottom_right.y - border), border, color);
-
-        let color = Color::from_hex_rgb(Classic::BACKGROUND);
-        self.graphics.draw_line((top_left.x + border, bottom_right.y - border - border_half), (bottom_right.x - border, bottom_right.y - border - border_half), border, color);
-        self.graphics.draw_line((bottom_right.x - border - border_half, top_left.y + border), (bottom_right.x - border - border_half, bottom_right.y - border), border, color);
-
-        let color = Color::from_hex_rgb(0xffffff);
-        self.graphics.draw_line((top_left.x + border, bottom_right.y - border_half), (bottom_right.x - border, bottom_right.y - border_half), border, color);
+        let color = Classic::LIGHT;
+        self.renderer.draw_line((top_left.x, top_left.y + border_half), (bottom_right.x - border, top_left.y + border_half), border, color);
+        self.renderer.draw_line((top_left.x + border_half, top_left.y), (top_left.x + border_half, bottom_right.y - border), border, color);
+        let color = Classic::DARK;
+        self.renderer.draw_line((top_left.x + border, top_left.y + border + border_half), (bottom_right.x - border, top_left.y + border + border_half), border, color);
+        self.renderer.draw_line((top_left.x + border + border_half, top_left.y + border), (top_left.x + border + border_half, bottom_right.y - border), border, color);
+
+        let color = Classic::BACKGROUND;
+        self.renderer.draw_line((top_left.x + border, bottom_right.y - border - border_half), (bottom_right.x - border, bottom_right.y - border - border_half), border, color);
+        self.renderer.draw_line((bottom_right.x - border - border_half, top_left.y + border), (bottom_right.x - border - border_half, bottom_right.y - border), border, color);
+
+        let color = 0xffffffff;
+        self.renderer.draw_line((top_left.x + border, bottom_right.y - border_half), (bottom_right.x - border, bottom_right.y - border_half), border, color);
     }
 
     fn draw_edit_caret(&mut self, rect: Rect<i32>, state: ViewState) {
         if !state.focused {
             return;
         }
-        let top_left = Vector2::new(rect.min.x as f32, rect.min.y as f32);
-        let bottom_right = Vector2::new(rect.max.x as f32, rect.max.y as f32);
-        let color = Color::from_hex_rgb(Classic::BLACK);
-        self.graphics.draw_rectangle(Rectangle::new(top_left, bottom_right), color);
+        let color = Classic::BLACK;
+        self.renderer.fill_rect(rect, color);
     }
 
     fn draw_checkbox_back(&mut self, rect: Rect<i32>, state: ViewState) {
@@ -209,9 +190,9 @@ impl<'h> Theme for Classic<'h> {
             let bottom_right = Vector2::new(rect.max.x as f32 - self.scale as f32 * 3.0, rect.max.y as f32 - self.scale as f32 * 3.0);
             let width = bottom_right.x - top_left.x;
             let height = bottom_right.y - top_left.y;
-            let color = Color::from_hex_rgb(Classic::BLACK);
-            self.graphics.draw_line((top_left.x, top_left.y + height / 2f32), (top_left.x + width / 3f32, bottom_right.y - height / 8f32), self.scale as f32, color);
-            self.graphics.draw_line((top_left.x + width / 3f32, bottom_right.y - height / 8f32), (bottom_right.x, top_left.y + height / 8f32), self.scale as f32, color);
+            let color = Classic::BLACK;
+            self.renderer.draw_line((top_left.x, top_left.y + height / 2f32), (top_left.x + width / 3f32, bottom_right.y - height / 8f32), self.scale as f32, color);
+            self.renderer.draw_line((top_left.x + width / 3f32, bottom_right.y - height / 8f32), (bottom_right.x, top_left.y + height / 8f32), self.scale as f32, color);
         }
     }
 
@@ -225,10 +206,8 @@ impl<'h> Theme for Classic<'h> {
 
     #[allow(unused)]
     fn draw_panel_back(&mut self, rect: Rect<i32>, state: ViewState) {
-        let top_left = Vector2::new(rect.min.x as f32, rect.min.y as f32);
-        let bottom_right = Vector2::new(rect.max.x as f32, rect.max.y as f32);
-        let color = Color::from_hex_rgb(Classic::BACKGROUND);
-        self.graphics.draw_rectangle(Rectangle::new(top_left, bottom_right), color);
+        let color = Classic::BACKGROUND;
+        self.renderer.fill_rect(rect, color);
     }
 
     #[allow(unused)]
@@ -236,24 +215,28 @@ impl<'h> Theme for Classic<'h> {
         let top_left = Vector2::new(rect.min.x as f32, rect.min.y as f32);
         let bottom_right = Vector2::new(rect.max.x as f32, rect.max.y as f32);
         let border: f32 = 1f32;
-        let color = Color::from_hex_rgb(0xff808080);
+        let color = 0xff808080;
         let half = 0.5f32;
-        //draw_rounded_rectangle(self.graphics, rect.min.x as f32, rect.min.y as f32, rect.max.x as f32, rect.max.y as f32, 16f32, 2f32, color);
-        self.graphics.draw_line((top_left.x, top_left.y + border - half), (bottom_right.x, top_left.y + border - half), border, color);
-        self.graphics.draw_line((top_left.x, bottom_right.y - half), (bottom_right.x, bottom_right.y - half), border, color);
-        self.graphics.draw_line((top_left.x + half, top_left.y + border), (top_left.x + half, bottom_right.y + border), border, color);
-        self.graphics.draw_line((bottom_right.x - half, top_left.y + border + half), (bottom_right.x - half, bottom_right.y + border - half), border, color);
+        //draw_rounded_rectangle(self.renderer, rect.min.x as f32, rect.min.y as f32, rect.max.x as f32, rect.max.y as f32, 16f32, 2f32, color);
+        self.renderer.draw_line((top_left.x, top_left.y + border - half), (bottom_right.x, top_left.y + border - half), border, color);
+        self.renderer.draw_line((top_left.x, bottom_right.y - half), (bottom_right.x, bottom_right.y - half), border, color);
+        self.renderer.draw_line((top_left.x + half, top_left.y + border), (top_left.x + half, bottom_right.y + border), border, color);
+        self.renderer.draw_line((bottom_right.x - half, top_left.y + border + half), (bottom_right.x - half, bottom_right.y + border - half), border, color);
     }
 
     fn draw_text(&mut self, x: f32, y: f32, color: u32, text: &Rc<FormattedTextBlock>) {
-        let color = Color::from_hex_rgb(color);
-        self.graphics.draw_text((x, y), color, text);
+        self.renderer.draw_text((x, y), color, text);
     }
 
     fn draw_rect(&mut self, rect: Rect<i32>, color: u32) {
-        let top_left = Vector2::new(rect.min.x as f32, rect.min.y as f32);
-        let bottom_right = Vector2::new(rect.max.x as f32, rect.max.y as f32);
-        let color = Color::from_hex_argb(color);
-        self.graphics.draw_rectangle(Rectangle::new(top_left, bottom_right), color);
+        self.renderer.fill_rect(rect, color);
+    }
+
+    fn draw_gradient(&mut self, rect: Rect<i32>, colors: [u32; 4]) {
+        self.renderer.fill_gradient(rect, colors);
+    }
+
+    fn draw_image(&mut self, rect: Rect<i32>, image: &Rc<Image>, mode: &ImageMode) {
+        self.renderer.draw_image_mode(rect, image, mode);
     }
 }
\ No newline at end of file
@@ -0,0 +1,194 @@
+use std::rc::Rc;
+use speedy2d::font::FormattedTextBlock;
+use gui::render::{Image, Renderer};
+use gui::styles::selector::{DrawState, ImageMode, MainSelector};
+use gui::themes::{Theme, ThemeFactory, Typeface, ViewState};
+use gui::types::Rect;
+
+/// Flat dark theme with rounded corners, easy for eyes at night.
+#[allow(unused)]
+pub struct Dark<'h> {
+    renderer: &'h mut dyn Renderer,
+    width: i32,
+    height: i32,
+    scale: f64
+}
+
+#[allow(dead_code)]
+impl<'h> Dark<'h> {
+    const BACKGROUND: u32 = 0xff202124;
+    const PANEL: u32 = 0xff26282b;
+    const FIELD: u32 = 0xff17181a;
+    const BUTTON: u32 = 0xff3a3d41;
+    const BUTTON_HOVERED: u32 = 0xff46494e;
+    const BUTTON_PRESSED: u32 = 0xff2d3033;
+    const BORDER: u32 = 0xff505358;
+    const ACCENT: u32 = 0xff4a90d9;
+    const TEXT: u32 = 0xffe0e0e0;
+    const TEXT_DISABLED: u32 = 0xff7a7d82;
+
+    pub fn new(renderer: &'h mut dyn Renderer, width: i32, height: i32, scale: f64) -> Self {
+        Dark { renderer, width, height, scale }
+    }
+
+    fn radius(&self) -> f32 {
+        (self.scale * 3f64) as f32
+    }
+
+    fn border_color(state: &ViewState) -> u32 {
+        match state.focused {
+            true => Dark::ACCENT,
+            false => Dark::BORDER
+        }
+    }
+}
+
+/// Makes `Dark` themes for `UI`.
+pub struct DarkFactory;
+
+impl ThemeFactory for DarkFactory {
+    fn create<'r>(&self, renderer: &'r mut dyn Renderer, width: i32, height: i32, scale: f64) -> Box<dyn Theme + 'r> {
+        Box::new(Dark::new(renderer, width, height, scale))
+    }
+
+    fn typeface(&self) -> Typeface {
+        Dark::typeface()
+    }
+}
+
+impl<'h> Theme for Dark<'h> {
+    fn clear_screen(&mut self) {
+        self.renderer.clear_screen(Dark::BACKGROUND);
+    }
+
+    fn typeface() -> Typeface {
+        Typeface::default()
+    }
+
+    fn get_back_color(&self, state: ViewState, selector: &MainSelector) -> u32 {
+        if let Some(s) = selector.get_state(&state) {
+            match s {
+                DrawState::Transparent => return 0x00000000,
+                DrawState::Color(c) => return *c,
+                _ => {}
+            }
+        }
+        Dark::BACKGROUND
+    }
+
+    fn get_text_color(&self, state: ViewState, selector: &MainSelector) -> u32 {
+        if let Some(s) = selector.get_state(&state) {
+            match s {
+                DrawState::Transparent => return 0x00000000,
+                DrawState::Color(c) => return *c,
+                _ => {}
+            }
+        }
+        if !state.enabled {
+            return Dark::TEXT_DISABLED;
+        }
+        Dark::TEXT
+    }
+
+    fn set_clip(&mut self, rect: Rect<i32>) {
+        self.renderer.set_clip(rect);
+    }
+
+    fn clip_rect(&mut self, rect: Rect<i32>) -> Rect<i32> {
+        self.renderer.clip_rect(rect)
+    }
+
+    fn push_clip(&mut self) {
+        self.renderer.push_clip();
+    }
+
+    fn pop_clip(&mut self) {
+        self.renderer.pop_clip();
+    }
+
+    fn draw_button_back(&mut self, rect: Rect<i32>, state: ViewState) {
+        let color = if state.pressed && state.hovered {
+            Dark::BUTTON_PRESSED
+        } else if state.hovered {
+            Dark::BUTTON_HOVERED
+        } else {
+            Dark::BUTTON
+        };
+        let radius = self.radius();
+        self.renderer.fill_rounded_rect(rect, radius, color);
+    }
+
+    fn draw_button_body(&mut self, rect: Rect<i32>, state: ViewState) {
+        let radius = self.radius();
+        self.renderer.stroke_rounded_rect(rect, radius, self.scale as f32, Dark::border_color(&state));
+    }
+
+    #[allow(unused)]
+    fn draw_edit_back(&mut self, rect: Rect<i32>, state: ViewState) {
+        let radius = self.radius();
+        self.renderer.fill_rounded_rect(rect, radius, Dark::FIELD);
+    }
+
+    fn draw_edit_body(&mut self, rect: Rect<i32>, state: ViewState) {
+        let radius = self.radius();
+        self.renderer.stroke_rounded_rect(rect, radius, self.scale as f32, Dark::border_color(&state));
+    }
+
+    fn draw_edit_caret(&mut self, rect: Rect<i32>, state: ViewState) {
+        if !state.focused {
+            return;
+        }
+        self.renderer.fill_rect(rect, Dark::TEXT);
+    }
+
+    fn draw_checkbox_back(&mut self, rect: Rect<i32>, state: ViewState) {
+        self.draw_edit_back(rect, state);
+    }
+
+    fn draw_checkbox_body(&mut self, rect: Rect<i32>, state: ViewState) {
+        self.draw_edit_body(rect, state);
+        if state.checked {
+            let scale = self.scale as f32;
+            let (x1, y1) = (rect.min.x as f32 + scale * 3.0, rect.min.y as f32 + scale * 3.0);
+            let (x2, y2) = (rect.max.x as f32 - scale * 3.0, rect.max.y as f32 - scale * 3.0);
+            let width = x2 - x1;
+            let height = y2 - y1;
+            self.renderer.draw_line((x1, y1 + height / 2f32), (x1 + width / 3f32, y2 - height / 8f32), scale * 2f32, Dark::ACCENT);
+            self.renderer.draw_line((x1 + width / 3f32, y2 - height / 8f32), (x2, y1 + height / 8f32), scale * 2f32, Dark::ACCENT);
+        }
+    }
+
+    fn draw_list_back(&mut self, rect: Rect<i32>, state: ViewState) {
+        self.draw_edit_back(rect, state);
+    }
+
+    fn draw_list_body(&mut self, rect: Rect<i32>, state: ViewState) {
+        self.draw_edit_body(rect, state);
+    }
+
+    #[allow(unused)]
+    fn draw_panel_back(&mut self, rect: Rect<i32>, state: ViewState) {
+        self.renderer.fill_rect(rect, Dark::PANEL);
+    }
+
+    #[allow(unused)]
+    fn draw_panel_body(&mut self, rect: Rect<i32>, state: ViewState) {
+        self.renderer.stroke_rect(rect, 1f32, Dark::BORDER);
+    }
+
+    fn draw_text(&mut self, x: f32, y: f32, color: u32, text: &Rc<FormattedTextBlock>) {
+        self.renderer.draw_text((x, y), color, text);
+    }
+
+    fn draw_rect(&mut self, rect: Rect<i32>, color: u32) {
+        self.renderer.fill_rect(rect, color);
+    }
+
+    fn draw_gradient(&mut self, rect: Rect<i32>, colors: [u32; 4]) {
+        self.renderer.fill_gradient(rect, colors);
+    }
+
+    fn draw_image(&mut self, rect: Rect<i32>, image: &Rc<Image>, mode: &ImageMode) {
+        self.renderer.draw_image_mode(rect, image, mode);
+    }
+}
@@ -1,12 +1,27 @@
 mod classic;
+mod dark;
+mod recording;
+mod styled;
 mod utils;
 
 use std::rc::Rc;
 use speedy2d::font::FormattedTextBlock;
-use gui::styles::selector::MainSelector;
-pub use super::themes::classic::Classic;
+use gui::assets::get_image;
+use gui::styles::selector::{DrawState, ImageMode, MainSelector};
+pub use super::themes::classic::{Classic, ClassicFactory};
+pub use super::themes::dark::{Dark, DarkFactory};
+pub use super::themes::recording::{DrawCommand, Recorder};
+pub use super::themes::styled::{StyleProps, Styled, StyledFactory, ThemeSpec, WidgetStyle};
+use gui::render::{Image, Renderer};
 use gui::types::Rect;
 
+/// Makes themes of one kind, a new theme for every painted frame.
+pub trait ThemeFactory {
+    fn create<'r>(&self, renderer: &'r mut dyn Renderer, width: i32, height: i32, scale: f64) -> Box<dyn Theme + 'r>;
+    /// The typeface that views use by default with this theme
+    fn typeface(&self) -> Typeface;
+}
+
 pub trait Theme {
     fn clear_screen(&mut self);
     fn typeface() -> Typeface where Self: Sized;
@@ -18,7 +33,6 @@ pub trait Theme {
     fn pop_clip(&mut self);
     fn draw_button_back(&mut self, rect: Rect<i32>, state: ViewState);
     fn draw_button_body(&mut self, rect: Rect<i32>, state: ViewState);
-    fn draw_button_text(&mut self, rect: Rect<i32>, state: ViewState, size: usize, text: &str);
     fn draw_edit_back(&mut self, rect: Rect<i32>, state: ViewState);
     fn draw_edit_body(&mut self, rect: Rect<i32>, state: ViewState);
     fn draw_edit_caret(&mut self, rect: Rect<i32>, state: ViewState);
@@ -30,10 +44,39 @@ pub trait Theme {
     fn draw_panel_body(&mut self, rect: Rect<i32>, state: ViewState);
     fn draw_text(&mut self, x: f32, y: f32, color: u32, text: &Rc<FormattedTextBlock>);
     fn draw_rect(&mut self, rect: Rect<i32>, color: u32);
+    /// Fills the rect with colors of its corners, clockwise from the top left one
+    fn draw_gradient(&mut self, rect: Rect<i32>, colors: [u32; 4]);
+    fn draw_image(&mut self, rect: Rect<i32>, image: &Rc<Image>, mode: &ImageMode);
+
+    /// Draws the background that styles set for this state of the view.
+    /// Returns false if there is no such background, and the widget must draw its usual one.
+    fn draw_styled_back(&mut self, rect: Rect<i32>, state: ViewState, selector: &MainSelector) -> bool {
+        match selector.get_state(&state) {
+            Some(DrawState::Transparent) => true,
+            Some(DrawState::Color(color)) => {
+                self.draw_rect(rect, *color);
+                true
+            }
+            Some(DrawState::Gradient(c1, c2, c3, c4)) => {
+                self.draw_gradient(rect, [*c1, *c2, *c3, *c4]);
+                true
+            }
+            Some(DrawState::Image(path, mode)) => {
+                match get_image(path) {
+                    None => false,
+                    Some(image) => {
+                        self.draw_image(rect, &image, mode);
+                        true
+                    }
+                }
+            }
+            None => false
+        }
+    }
 }
 
 #[allow(unused)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum FontStyle {
     Regular,
     Bold,
@@ -64,7 +107,7 @@ impl From<String> for FontStyle {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Typeface {
     pub font_name: String,
     pub font_style: FontStyle
@@ -77,7 +120,7 @@ impl Default for Typeface {
 }
 
 #[allow(unused)]
-#[derive(Clone, Copy, Eq, PartialEq, Hash)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub struct ViewState {
     pub enabled: bool,
     pub focusable: bool,
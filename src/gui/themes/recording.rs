@@ -0,0 +1,354 @@
+use std::cmp::{max, min};
+use std::collections::VecDeque;
+use std::fmt;
+use std::rc::Rc;
+use speedy2d::font::FormattedTextBlock;
+use gui::assets::get_text_source;
+use gui::render::Image;
+use gui::styles::selector::{DrawState, ImageMode, MainSelector};
+use gui::themes::{Theme, Typeface, ViewState};
+use gui::types::{rect, Rect};
+use gui::types;
+
+/// One call to the theme, as it was recorded by `Recorder`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DrawCommand {
+    ClearScreen,
+    SetClip(Rect<i32>),
+    ClipRect(Rect<i32>),
+    PushClip,
+    PopClip,
+    ButtonBack(Rect<i32>, ViewState),
+    ButtonBody(Rect<i32>, ViewState),
+    EditBack(Rect<i32>, ViewState),
+    EditBody(Rect<i32>, ViewState),
+    EditCaret(Rect<i32>, ViewState),
+    CheckboxBack(Rect<i32>, ViewState),
+    CheckboxBody(Rect<i32>, ViewState),
+    ListBack(Rect<i32>, ViewState),
+    ListBody(Rect<i32>, ViewState),
+    PanelBack(Rect<i32>, ViewState),
+    PanelBody(Rect<i32>, ViewState),
+    /// Text is known only if the block was laid out by `assets::layout_text()`
+    Text { x: f32, y: f32, color: u32, text: Option<String> },
+    Rect(Rect<i32>, u32),
+    Gradient(Rect<i32>, [u32; 4]),
+    /// Rect, size of the image and how it fills the rect
+    Image(Rect<i32>, (u32, u32), ImageMode)
+}
+
+struct StateFlags(ViewState);
+
+impl fmt::Display for StateFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = &self.0;
+        let flags = [
+            (!state.enabled, "disabled"),
+            (state.focusable, "focusable"),
+            (state.focused, "focused"),
+            (state.hovered, "hovered"),
+            (state.pressed, "pressed"),
+            (state.checked, "checked")
+        ];
+        let names = flags.iter().filter(|(set, _)| *set).map(|(_, name)| *name).collect::<Vec<_>>();
+        match names.is_empty() {
+            true => write!(f, "-"),
+            false => write!(f, "{}", names.join(","))
+        }
+    }
+}
+
+struct RectText(Rect<i32>);
+
+impl fmt::Display for RectText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rect = &self.0;
+        write!(f, "{},{} {},{}", rect.min.x, rect.min.y, rect.max.x, rect.max.y)
+    }
+}
+
+/// Formats the command as one line of text, like `button_body 0,0 80,24 focusable,hovered`
+impl fmt::Display for DrawCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let widget = |f: &mut fmt::Formatter<'_>, name: &str, rect: &Rect<i32>, state: &ViewState| {
+            write!(f, "{} {} {}", name, RectText(*rect), StateFlags(*state))
+        };
+        match self {
+            DrawCommand::ClearScreen => write!(f, "clear_screen"),
+            DrawCommand::SetClip(r) => write!(f, "set_clip {}", RectText(*r)),
+            DrawCommand::ClipRect(r) => write!(f, "clip_rect {}", RectText(*r)),
+            DrawCommand::PushClip => write!(f, "push_clip"),
+            DrawCommand::PopClip => write!(f, "pop_clip"),
+            DrawCommand::ButtonBack(r, s) => widget(f, "button_back", r, s),
+            DrawCommand::ButtonBody(r, s) => widget(f, "button_body", r, s),
+            DrawCommand::EditBack(r, s) => widget(f, "edit_back", r, s),
+            DrawCommand::EditBody(r, s) => widget(f, "edit_body", r, s),
+            DrawCommand::EditCaret(r, s) => widget(f, "edit_caret", r, s),
+            DrawCommand::CheckboxBack(r, s) => widget(f, "checkbox_back", r, s),
+            DrawCommand::CheckboxBody(r, s) => widget(f, "checkbox_body", r, s),
+            DrawCommand::ListBack(r, s) => widget(f, "list_back", r, s),
+            DrawCommand::ListBody(r, s) => widget(f, "list_body", r, s),
+            DrawCommand::PanelBack(r, s) => widget(f, "panel_back", r, s),
+            DrawCommand::PanelBody(r, s) => widget(f, "panel_body", r, s),
+            DrawCommand::Text { x, y, color, text } => {
+                write!(f, "text {},{} #{:08x}", x, y, color)?;
+                match text {
+                    None => Ok(()),
+                    Some(text) => write!(f, " {:?}", text)
+                }
+            }
+            DrawCommand::Rect(r, color) => write!(f, "rect {} #{:08x}", RectText(*r), color),
+            DrawCommand::Gradient(r, colors) => {
+                write!(f, "gradient {}", RectText(*r))?;
+                for color in colors.iter() {
+                    write!(f, " #{:08x}", color)?;
+                }
+                Ok(())
+            }
+            DrawCommand::Image(r, (width, height), mode) => write!(f, "image {} {}x{} {:?}", RectText(*r), width, height, mode)
+        }
+    }
+}
+
+/// Theme that does not draw anything, but records every call to it.
+/// Useful to check what views paint, and in what state, without any pixels.
+#[allow(unused)]
+pub struct Recorder {
+    width: i32,
+    height: i32,
+    current_clip: Rect<i32>,
+    clip_stack: VecDeque<Rect<i32>>,
+    commands: Vec<DrawCommand>
+}
+
+#[allow(dead_code)]
+impl Recorder {
+    const BACKGROUND: u32 = 0xffd4d0c8;
+
+    pub fn new(width: i32, height: i32) -> Self {
+        let current_clip = rect((0, 0), (width, height));
+        Recorder { width, height, current_clip, clip_stack: VecDeque::new(), commands: Vec::new() }
+    }
+
+    pub fn commands(&self) -> &[DrawCommand] {
+        &self.commands
+    }
+
+    pub fn clear(&mut self) {
+        self.commands.clear();
+    }
+
+    /// Serializes all recorded commands, one per line
+    pub fn to_text(&self) -> String {
+        let mut result = String::new();
+        for command in self.commands.iter() {
+            result.push_str(&command.to_string());
+            result.push('\n');
+        }
+        result
+    }
+}
+
+impl Theme for Recorder {
+    fn clear_screen(&mut self) {
+        self.commands.push(DrawCommand::ClearScreen);
+    }
+
+    fn typeface() -> Typeface {
+        Typeface::default()
+    }
+
+    fn get_back_color(&self, state: ViewState, selector: &MainSelector) -> u32 {
+        if let Some(s) = selector.get_state(&state) {
+            match s {
+                DrawState::Transparent => return 0x00000000,
+                DrawState::Color(c) => return *c,
+                _ => {}
+            }
+        }
+        Recorder::BACKGROUND
+    }
+
+    fn get_text_color(&self, state: ViewState, selector: &MainSelector) -> u32 {
+        if let Some(s) = selector.get_state(&state) {
+            match s {
+                DrawState::Transparent => return 0x00000000,
+                DrawState::Color(c) => return *c,
+                _ => {}
+            }
+        }
+        if !state.enabled {
+            return 0xff202020;
+        }
+        0xff000000
+    }
+
+    fn set_clip(&mut self, rect: Rect<i32>) {
+        self.current_clip = rect;
+        self.commands.push(DrawCommand::SetClip(rect));
+    }
+
+    fn clip_rect(&mut self, rect: Rect<i32>) -> Rect<i32> {
+        let min_x = max(rect.min.x, self.current_clip.min.x);
+        let max_x = min(rect.max.x, self.current_clip.max.x);
+        let min_y = max(rect.min.y, self.current_clip.min.y);
+        let max_y = min(rect.max.y, self.current_clip.max.y);
+        let rect = types::rect((min_x, min_y), (max_x, max_y));
+        self.current_clip = rect;
+        self.commands.push(DrawCommand::ClipRect(rect));
+        rect
+    }
+
+    fn push_clip(&mut self) {
+        self.clip_stack.push_back(self.current_clip);
+        self.commands.push(DrawCommand::PushClip);
+    }
+
+    fn pop_clip(&mut self) {
+        if let Some(clip) = self.clip_stack.pop_back() {
+            self.current_clip = clip;
+        }
+        self.commands.push(DrawCommand::PopClip);
+    }
+
+    fn draw_button_back(&mut self, rect: Rect<i32>, state: ViewState) {
+        self.commands.push(DrawCommand::ButtonBack(rect, state));
+    }
+
+    fn draw_button_body(&mut self, rect: Rect<i32>, state: ViewState) {
+        self.commands.push(DrawCommand::ButtonBody(rect, state));
+    }
+
+    fn draw_edit_back(&mut self, rect: Rect<i32>, state: ViewState) {
+        self.commands.push(DrawCommand::EditBack(rect, state));
+    }
+
+    fn draw_edit_body(&mut self, rect: Rect<i32>, state: ViewState) {
+        self.commands.push(DrawCommand::EditBody(rect, state));
+    }
+
+    fn draw_edit_caret(&mut self, rect: Rect<i32>, state: ViewState) {
+        self.commands.push(DrawCommand::EditCaret(rect, state));
+    }
+
+    fn draw_checkbox_back(&mut self, rect: Rect<i32>, state: ViewState) {
+        self.commands.push(DrawCommand::CheckboxBack(rect, state));
+    }
+
+    fn draw_checkbox_body(&mut self, rect: Rect<i32>, state: ViewState) {
+        self.commands.push(DrawCommand::CheckboxBody(rect, state));
+    }
+
+    fn draw_list_back(&mut self, rect: Rect<i32>, state: ViewState) {
+        self.commands.push(DrawCommand::ListBack(rect, state));
+    }
+
+    fn draw_list_body(&mut self, rect: Rect<i32>, state: ViewState) {
+        self.commands.push(DrawCommand::ListBody(rect, state));
+    }
+
+    fn draw_panel_back(&mut self, rect: Rect<i32>, state: ViewState) {
+        self.commands.push(DrawCommand::PanelBack(rect, state));
+    }
+
+    fn draw_panel_body(&mut self, rect: Rect<i32>, state: ViewState) {
+        self.commands.push(DrawCommand::PanelBody(rect, state));
+    }
+
+    fn draw_text(&mut self, x: f32, y: f32, color: u32, text: &Rc<FormattedTextBlock>) {
+        let text = get_text_source(text).map(|source| source.chars.iter().collect::<String>());
+        self.commands.push(DrawCommand::Text { x, y, color, text });
+    }
+
+    fn draw_rect(&mut self, rect: Rect<i32>, color: u32) {
+        self.commands.push(DrawCommand::Rect(rect, color));
+    }
+
+    fn draw_gradient(&mut self, rect: Rect<i32>, colors: [u32; 4]) {
+        self.commands.push(DrawCommand::Gradient(rect, colors));
+    }
+
+    fn draw_image(&mut self, rect: Rect<i32>, image: &Rc<Image>, mode: &ImageMode) {
+        self.commands.push(DrawCommand::Image(rect, (image.width(), image.height()), *mode));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use speedy2d::dimen::Vector2;
+    use speedy2d::window::MouseButton;
+    use gui::assets::set_example_assets;
+    use gui::themes::{DrawCommand, Recorder, Theme};
+    use gui::ui::UI;
+    use gui::views::List;
+
+    const LAYOUT: &str = r#"<Frame id="main" width="max" height="max" font="Noto Sans" direction="vertical">
+        <Button id="button" text="Button"/>
+        <Edit id="edit" width="200" text="Text"/>
+        <List id="list" width="200" height="200"/>
+    </Frame>"#;
+
+    fn load() -> UI {
+        set_example_assets();
+        let mut ui = UI::from_xml(LAYOUT, 400, 400, Recorder::typeface()).unwrap();
+        ui.layout(400, 400, 1.0);
+        ui
+    }
+
+    fn record(ui: &UI) -> Recorder {
+        let mut recorder = Recorder::new(400, 400);
+        ui.paint(&mut recorder);
+        recorder
+    }
+
+    fn center(ui: &UI, id: &str) -> Vector2<i32> {
+        let rect = ui.get_view(id).unwrap().borrow().get_rect();
+        Vector2::new((rect.min.x + rect.max.x) / 2, (rect.min.y + rect.max.y) / 2)
+    }
+
+    #[test]
+    fn focused_edit_draws_caret() {
+        let mut ui = load();
+        let has_caret = |recorder: &Recorder| recorder.commands().iter().any(|c| matches!(c, DrawCommand::EditCaret(_, s) if s.focused));
+        assert!(!has_caret(&record(&ui)));
+
+        let position = center(&ui, "edit");
+        ui.on_mouse_button_down(position, MouseButton::Left);
+        assert!(has_caret(&record(&ui)));
+    }
+
+    #[test]
+    fn pressed_button_uses_pressed_state() {
+        let mut ui = load();
+        let pressed = |recorder: &Recorder| recorder.commands().iter().any(|c| matches!(c, DrawCommand::ButtonBody(_, s) if s.pressed));
+        assert!(!pressed(&record(&ui)));
+
+        let position = center(&ui, "button");
+        ui.on_mouse_button_down(position, MouseButton::Left);
+        assert!(pressed(&record(&ui)));
+
+        ui.on_mouse_button_up(position, MouseButton::Left);
+        assert!(!pressed(&record(&ui)));
+    }
+
+    #[test]
+    fn list_highlights_selected_row() {
+        let ui = load();
+        let list = ui.get_view("list").unwrap();
+        if let Some(list) = list.borrow_mut().downcast_mut::<List>() {
+            list.set_items(vec![String::from("One"), String::from("Two"), String::from("Three")]);
+            list.select_item(1);
+        }
+        let recorder = record(&ui);
+        let commands = recorder.commands();
+        let highlight = commands.iter().position(|c| matches!(c, DrawCommand::Rect(_, 0xff0000c0)));
+        let highlight = highlight.expect("Selected row is not highlighted");
+        match &commands[highlight + 1] {
+            DrawCommand::Text { color, text, .. } => {
+                assert_eq!(*color, 0xffffffff);
+                assert_eq!(text.as_deref(), Some("Two"));
+            }
+            command => panic!("Expected text of the selected row, got {}", command)
+        }
+    }
+}
@@ -0,0 +1,407 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use speedy2d::font::FormattedTextBlock;
+use gui::assets::get_file;
+use gui::common::get_unescaped_attributes;
+use gui::styles::parse_color;
+use gui::render::{Image, Renderer};
+use gui::styles::selector::{DrawState, ImageMode, MainSelector, StateMatch};
+use gui::themes::{FontStyle, Theme, ThemeFactory, Typeface, ViewState};
+use gui::types::Rect;
+use gui::ui::{LoadError, Position};
+
+/// Widgets that can be styled in theme file
+const WIDGETS: [&str; 7] = ["Default", "Window", "Button", "Edit", "CheckBox", "List", "Panel"];
+
+/// Properties of one widget, all of them are optional, so that they can override each other.
+#[derive(Clone, Debug, Default)]
+pub struct StyleProps {
+    pub background: Option<u32>,
+    pub border: Option<u32>,
+    pub border_width: Option<f32>,
+    pub radius: Option<f32>,
+    pub text: Option<u32>,
+    /// Color of check mark and text caret
+    pub mark: Option<u32>
+}
+
+impl StyleProps {
+    /// Takes every property that is set in `other`
+    fn merge(&mut self, other: &StyleProps) {
+        if other.background.is_some() { self.background = other.background; }
+        if other.border.is_some() { self.border = other.border; }
+        if other.border_width.is_some() { self.border_width = other.border_width; }
+        if other.radius.is_some() { self.radius = other.radius; }
+        if other.text.is_some() { self.text = other.text; }
+        if other.mark.is_some() { self.mark = other.mark; }
+    }
+}
+
+/// Style of one widget with its variations for different states.
+#[derive(Clone, Debug, Default)]
+pub struct WidgetStyle {
+    pub props: StyleProps,
+    /// Variations are applied in order, so the later ones win
+    pub states: Vec<(StateMatch, StyleProps)>
+}
+
+/// Theme description, that is loaded from XML file like this:
+/// ```xml
+/// <Theme name="Night" font="NotoSans" font_style="Regular">
+///     <Color name="accent" value="#4a90d9"/>
+///     <Style widget="Button" background="#3a3d41" border="#505358" border_width="1" radius="4" text="#e0e0e0">
+///         <State hovered="true" background="#46494e"/>
+///         <State focused="true" border="accent"/>
+///     </Style>
+/// </Theme>
+/// ```
+/// Widgets are `Window`, `Button`, `Edit`, `CheckBox`, `List` and `Panel`, and the `Default` style is applied under all of them.
+/// Colors are `#rrggbb`, `#aarrggbb`, `transparent` or names of colors defined earlier.
+/// Sizes are in dips, and get scaled when painted.
+#[derive(Clone, Debug, Default)]
+pub struct ThemeSpec {
+    pub name: String,
+    pub typeface: Typeface,
+    pub colors: HashMap<String, u32>,
+    pub styles: HashMap<String, WidgetStyle>
+}
+
+#[allow(dead_code)]
+impl ThemeSpec {
+    /// Loads the theme file with the assets provider, see `from_xml()`.
+    /// The theme is named by its path, if the file does not give a name.
+    pub fn load(path: &str, warnings: &mut Vec<LoadError>) -> Result<ThemeSpec, LoadError> {
+        let file_error = |message: String| LoadError::File { path: path.to_owned(), message };
+        let bytes = get_file(path).ok_or_else(|| file_error(String::from("Theme is not found")))?;
+        let xml = String::from_utf8(bytes).map_err(|e| file_error(format!("Theme is not UTF-8: {}", e)))?;
+        let mut spec = ThemeSpec::from_xml(&xml, warnings)?;
+        if spec.name.is_empty() {
+            spec.name = path.to_owned();
+        }
+        Ok(spec)
+    }
+
+    /// Parses the theme, the error tells where the XML is wrong.
+    /// Wrong elements and attributes are skipped, and added to `warnings`.
+    pub fn from_xml(xml: &str, warnings: &mut Vec<LoadError>) -> Result<ThemeSpec, LoadError> {
+        let mut spec = ThemeSpec::default();
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true);
+        let mut widget: Option<String> = None;
+
+        loop {
+            let (e, empty) = match reader.read_event() {
+                Ok(Event::Start(e)) => (e, false),
+                Ok(Event::Empty(e)) => (e, true),
+                Ok(Event::End(e)) => {
+                    if e.name().0 == b"Style" {
+                        widget = None;
+                    }
+                    continue;
+                }
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(LoadError::Syntax { position: Position::new(xml, reader.buffer_position()), message: e.to_string() }),
+                _ => continue
+            };
+            let position = Position::of_tag(xml, reader.buffer_position());
+            let tag = String::from_utf8_lossy(e.name().as_ref()).to_string();
+            let attributes = get_unescaped_attributes(&e);
+            let mut problems = Vec::new();
+            match tag.as_str() {
+                "Theme" => {
+                    for (name, value) in attributes {
+                        match name.as_str() {
+                            "name" => spec.name = value,
+                            "font" => spec.typeface.font_name = value,
+                            "font_style" => spec.typeface.font_style = FontStyle::from(value),
+                            &_ => problems.push((name, String::from("Unknown attribute of Theme")))
+                        }
+                    }
+                }
+                "Color" => {
+                    let name = attributes.iter().find(|(n, _)| n == "name").map(|(_, v)| v.clone());
+                    let value = attributes.iter().find(|(n, _)| n == "value").and_then(|(_, v)| spec.parse_color(v));
+                    match (name, value) {
+                        (Some(name), Some(value)) => { spec.colors.insert(name, value); }
+                        _ => problems.push((String::from("value"), String::from("Color must have a name and a valid value")))
+                    }
+                }
+                "Style" => {
+                    let name = attributes.iter().find(|(n, _)| n == "widget").map(|(_, v)| v.clone());
+                    match name {
+                        Some(name) if WIDGETS.contains(&name.as_str()) => {
+                            let props = spec.parse_props(&attributes, &["widget"], &mut problems);
+                            spec.styles.entry(name.clone()).or_default().props.merge(&props);
+                            if !empty {
+                                widget = Some(name);
+                            }
+                        }
+                        _ => problems.push((String::from("widget"), format!("Style must have one of widgets: {}", WIDGETS.join(", "))))
+                    }
+                }
+                "State" => match &widget {
+                    None => warnings.push(LoadError::Syntax { position, message: String::from("State must be inside of Style") }),
+                    Some(name) => {
+                        let mut state = StateMatch::default();
+                        let mut flags = Vec::new();
+                        let mut wrong_state = false;
+                        for (name, value) in attributes.iter() {
+                            match state.set_flag(name, value) {
+                                Ok(true) => flags.push(name.as_str()),
+                                Ok(false) => {}
+                                Err(message) => {
+                                    problems.push((name.clone(), message));
+                                    flags.push(name.as_str());
+                                    wrong_state = true;
+                                }
+                            }
+                        }
+                        let props = spec.parse_props(&attributes, &flags, &mut problems);
+                        // Without the flag the state would match every state of the widget
+                        if !wrong_state {
+                            spec.styles.entry(name.clone()).or_default().states.push((state, props));
+                        }
+                    }
+                },
+                _ => warnings.push(LoadError::UnknownType { position, tag: tag.clone() })
+            }
+            for (attribute, message) in problems {
+                warnings.push(LoadError::Attribute { position, tag: tag.clone(), attribute, message });
+            }
+        }
+        Ok(spec)
+    }
+
+    /// Parses the color as `#rrggbb`, `#aarrggbb`, `transparent` or a name of defined color
+    pub fn parse_color(&self, value: &str) -> Option<u32> {
+        parse_color(value).or_else(|| self.colors.get(value).cloned())
+    }
+
+    /// Parses properties of `<Style>` or `<State>`, wrong ones are added to `problems` with their messages
+    fn parse_props(&self, attributes: &[(String, String)], skip: &[&str], problems: &mut Vec<(String, String)>) -> StyleProps {
+        let mut props = StyleProps::default();
+        for (name, value) in attributes {
+            let valid = match name.as_str() {
+                "background" => { props.background = self.parse_color(value); props.background.is_some() }
+                "border" => { props.border = self.parse_color(value); props.border.is_some() }
+                "text" => { props.text = self.parse_color(value); props.text.is_some() }
+                "mark" => { props.mark = self.parse_color(value); props.mark.is_some() }
+                "border_width" => { props.border_width = value.parse().ok(); props.border_width.is_some() }
+                "radius" => { props.radius = value.parse().ok(); props.radius.is_some() }
+                name => {
+                    if !skip.contains(&name) {
+                        problems.push((name.to_owned(), String::from("Unknown style property")));
+                    }
+                    true
+                }
+            };
+            if !valid {
+                problems.push((name.clone(), format!("Wrong value {:?}", value)));
+            }
+        }
+        props
+    }
+
+    /// Gets the properties of the widget in given state, with `Default` style under them
+    pub fn resolve(&self, widget: &str, state: &ViewState) -> StyleProps {
+        let mut result = StyleProps::default();
+        for name in ["Default", widget].iter() {
+            if let Some(style) = self.styles.get(*name) {
+                result.merge(&style.props);
+                for (matcher, props) in style.states.iter() {
+                    if matcher.matches(state) {
+                        result.merge(props);
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Theme that draws everything as described by `ThemeSpec`.
+#[allow(unused)]
+pub struct Styled<'h> {
+    renderer: &'h mut dyn Renderer,
+    spec: Rc<ThemeSpec>,
+    width: i32,
+    height: i32,
+    scale: f64
+}
+
+#[allow(dead_code)]
+impl<'h> Styled<'h> {
+    pub fn new(renderer: &'h mut dyn Renderer, spec: Rc<ThemeSpec>, width: i32, height: i32, scale: f64) -> Self {
+        Styled { renderer, spec, width, height, scale }
+    }
+
+    fn draw_back(&mut self, widget: &str, rect: Rect<i32>, state: ViewState) {
+        let props = self.spec.resolve(widget, &state);
+        if let Some(color) = props.background {
+            let radius = props.radius.unwrap_or(0f32) * self.scale as f32;
+            self.renderer.fill_rounded_rect(rect, radius, color);
+        }
+    }
+
+    fn draw_body(&mut self, widget: &str, rect: Rect<i32>, state: ViewState) {
+        let props = self.spec.resolve(widget, &state);
+        let width = props.border_width.unwrap_or(0f32) * self.scale as f32;
+        if let (Some(color), true) = (props.border, width > 0f32) {
+            let radius = props.radius.unwrap_or(0f32) * self.scale as f32;
+            self.renderer.stroke_rounded_rect(rect, radius, width, color);
+        }
+    }
+}
+
+/// Makes `Styled` themes for `UI` from one description.
+pub struct StyledFactory {
+    spec: Rc<ThemeSpec>
+}
+
+impl StyledFactory {
+    pub fn new(spec: ThemeSpec) -> Self {
+        StyledFactory { spec: Rc::new(spec) }
+    }
+
+    pub fn get_spec(&self) -> &ThemeSpec {
+        &self.spec
+    }
+}
+
+impl ThemeFactory for StyledFactory {
+    fn create<'r>(&self, renderer: &'r mut dyn Renderer, width: i32, height: i32, scale: f64) -> Box<dyn Theme + 'r> {
+        Box::new(Styled::new(renderer, Rc::clone(&self.spec), width, height, scale))
+    }
+
+    fn typeface(&self) -> Typeface {
+        self.spec.typeface.clone()
+    }
+}
+
+impl<'h> Theme for Styled<'h> {
+    fn clear_screen(&mut self) {
+        let props = self.spec.resolve("Window", &ViewState::default());
+        self.renderer.clear_screen(props.background.unwrap_or(0xffffffff));
+    }
+
+    fn typeface() -> Typeface {
+        Typeface::default()
+    }
+
+    fn get_back_color(&self, state: ViewState, selector: &MainSelector) -> u32 {
+        if let Some(s) = selector.get_state(&state) {
+            match s {
+                DrawState::Transparent => return 0x00000000,
+                DrawState::Color(c) => return *c,
+                _ => {}
+            }
+        }
+        self.spec.resolve("Window", &state).background.unwrap_or(0xffffffff)
+    }
+
+    fn get_text_color(&self, state: ViewState, selector: &MainSelector) -> u32 {
+        if let Some(s) = selector.get_state(&state) {
+            match s {
+                DrawState::Transparent => return 0x00000000,
+                DrawState::Color(c) => return *c,
+                _ => {}
+            }
+        }
+        self.spec.resolve("Default", &state).text.unwrap_or(0xff000000)
+    }
+
+    fn set_clip(&mut self, rect: Rect<i32>) {
+        self.renderer.set_clip(rect);
+    }
+
+    fn clip_rect(&mut self, rect: Rect<i32>) -> Rect<i32> {
+        self.renderer.clip_rect(rect)
+    }
+
+    fn push_clip(&mut self) {
+        self.renderer.push_clip();
+    }
+
+    fn pop_clip(&mut self) {
+        self.renderer.pop_clip();
+    }
+
+    fn draw_button_back(&mut self, rect: Rect<i32>, state: ViewState) {
+        self.draw_back("Button", rect, state);
+    }
+
+    fn draw_button_body(&mut self, rect: Rect<i32>, state: ViewState) {
+        self.draw_body("Button", rect, state);
+    }
+
+    fn draw_edit_back(&mut self, rect: Rect<i32>, state: ViewState) {
+        self.draw_back("Edit", rect, state);
+    }
+
+    fn draw_edit_body(&mut self, rect: Rect<i32>, state: ViewState) {
+        self.draw_body("Edit", rect, state);
+    }
+
+    fn draw_edit_caret(&mut self, rect: Rect<i32>, state: ViewState) {
+        if !state.focused {
+            return;
+        }
+        let props = self.spec.resolve("Edit", &state);
+        if let Some(color) = props.mark.or(props.text) {
+            self.renderer.fill_rect(rect, color);
+        }
+    }
+
+    fn draw_checkbox_back(&mut self, rect: Rect<i32>, state: ViewState) {
+        self.draw_back("CheckBox", rect, state);
+    }
+
+    fn draw_checkbox_body(&mut self, rect: Rect<i32>, state: ViewState) {
+        self.draw_body("CheckBox", rect, state);
+        let props = self.spec.resolve("CheckBox", &state);
+        if let (true, Some(color)) = (state.checked, props.mark.or(props.text)) {
+            let scale = self.scale as f32;
+            let (x1, y1) = (rect.min.x as f32 + scale * 3.0, rect.min.y as f32 + scale * 3.0);
+            let (x2, y2) = (rect.max.x as f32 - scale * 3.0, rect.max.y as f32 - scale * 3.0);
+            let width = x2 - x1;
+            let height = y2 - y1;
+            let thickness = props.border_width.unwrap_or(1f32).max(1f32) * scale;
+            self.renderer.draw_line((x1, y1 + height / 2f32), (x1 + width / 3f32, y2 - height / 8f32), thickness, color);
+            self.renderer.draw_line((x1 + width / 3f32, y2 - height / 8f32), (x2, y1 + height / 8f32), thickness, color);
+        }
+    }
+
+    fn draw_list_back(&mut self, rect: Rect<i32>, state: ViewState) {
+        self.draw_back("List", rect, state);
+    }
+
+    fn draw_list_body(&mut self, rect: Rect<i32>, state: ViewState) {
+        self.draw_body("List", rect, state);
+    }
+
+    fn draw_panel_back(&mut self, rect: Rect<i32>, state: ViewState) {
+        self.draw_back("Panel", rect, state);
+    }
+
+    fn draw_panel_body(&mut self, rect: Rect<i32>, state: ViewState) {
+        self.draw_body("Panel", rect, state);
+    }
+
+    fn draw_text(&mut self, x: f32, y: f32, color: u32, text: &Rc<FormattedTextBlock>) {
+        self.renderer.draw_text((x, y), color, text);
+    }
+
+    fn draw_rect(&mut self, rect: Rect<i32>, color: u32) {
+        self.renderer.fill_rect(rect, color);
+    }
+
+    fn draw_gradient(&mut self, rect: Rect<i32>, colors: [u32; 4]) {
+        self.renderer.fill_gradient(rect, colors);
+    }
+
+    fn draw_image(&mut self, rect: Rect<i32>, image: &Rc<Image>, mode: &ImageMode) {
+        self.renderer.draw_image_mode(rect, image, mode);
+    }
+}
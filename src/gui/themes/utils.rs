@@ -1,8 +1,7 @@
-use speedy2d::color::Color;
-use speedy2d::Graphics2D;
+use gui::render::Renderer;
 
 #[allow(unused)]
-pub fn draw_rounded_rectangle(graphics: &mut Graphics2D, x1: f32, y1: f32, x2: f32, y2: f32, radius: f32, scale: f32, color: Color) {
+pub fn draw_rounded_rectangle<R: Renderer + ?Sized>(graphics: &mut R, x1: f32, y1: f32, x2: f32, y2: f32, radius: f32, scale: f32, color: u32) {
     let xmin = x1.min(x2);
     let xmax = x1.max(x2);
     let ymin = y1.min(y2);
@@ -24,7 +23,7 @@ pub fn draw_rounded_rectangle(graphics: &mut Graphics2D, x1: f32, y1: f32, x2: f
 }
 
 #[allow(unused)]
-fn draw_quarter_circle(graphics: &mut Graphics2D, x: f32, y: f32, radius: f32, quadrant: i32, scale: f32, color: Color) {
+fn draw_quarter_circle<R: Renderer + ?Sized>(graphics: &mut R, x: f32, y: f32, radius: f32, quadrant: i32, scale: f32, color: u32) {
     let mut xx = radius;
     let mut yy = 0f32;
     let mut decision = 1f32 - xx;
@@ -63,7 +62,7 @@ fn draw_quarter_circle(graphics: &mut Graphics2D, x: f32, y: f32, radius: f32, q
 }
 
 #[allow(unused)]
-pub fn draw_dashed_rectangle(graphics: &mut Graphics2D, x1: f32, y1: f32, x2: f32, y2: f32, dash_len: f32, scale: f32, color: Color) {
+pub fn draw_dashed_rectangle<R: Renderer + ?Sized>(graphics: &mut R, x1: f32, y1: f32, x2: f32, y2: f32, dash_len: f32, scale: f32, color: u32) {
     let mut x = x1;
     let mut y = y1;
 
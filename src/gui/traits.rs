@@ -1,14 +1,15 @@
-use std::cell::RefCell;
+use std::cell::{RefCell, RefMut};
 use std::rc::{Rc, Weak};
 use downcast_rs::Downcast;
 use speedy2d::dimen::Vector2;
 use speedy2d::window::{KeyScancode, ModifiersState, MouseButton, VirtualKeyCode};
 use super::events::EventType;
+use super::properties::Property;
 use super::ui::UI;
 use super::themes::{Theme, ViewState};
-use super::types::{Rect, Point};
+use super::types::{Rect, Point, rect};
 use super::themes::Typeface;
-use super::views::{Borders, Dimension};
+use super::views::{Borders, Dimension, FieldsMain};
 
 pub type Element = Rc<RefCell<dyn View>>;
 pub type WeakElement = Weak<RefCell<dyn View>>;
@@ -17,11 +18,36 @@ pub type WeakElement = Weak<RefCell<dyn View>>;
 //pub type WeakParent = Weak<RefCell<dyn Container>>;
 
 pub trait View: Downcast {
-    fn set_any(&mut self, name: &str, value: &str);
+    /// Sets the property by its name, as it is written in XML.
+    /// Returns the message if the value is wrong for this property.
+    fn set_any(&mut self, name: &str, value: &str) -> Result<(), String>;
+    /// Gets the property by its name, in the form that `set_any()` takes.
+    /// Returns `None` for unknown properties and values that are not set.
+    fn get_any(&self, name: &str) -> Option<String> {
+        self.get_main()?.get_any(name)
+    }
+    /// All properties that `set_any()` understands.
+    /// Empty list means that the view does not tell them, and they are not checked.
+    fn properties() -> Vec<Property> where Self: Sized { Vec::new() }
     fn set_parent(&self, parent: Option<WeakElement>);
     fn get_parent(&self) -> Option<Element>;
-    #[allow(unused)]
-    fn layout_content(&mut self, x: i32, y: i32, width: i32, height: i32, typeface: &Typeface, scale: f64) -> Rect<i32>;
+    /// First pass of layout: calculates the size of the view with padding, but without margins.
+    /// Parent gives the space for the view with its margins, percents are taken from it.
+    /// Views lay out their content here, and keep it for `arrange()`.
+    fn measure(&mut self, width: i32, height: i32, typeface: &Typeface, scale: f64) -> (i32, i32);
+    /// Second pass of layout: places the view at the rect, that parent has chosen from the measured size.
+    /// Containers place their children here.
+    #[allow(unused_variables)]
+    fn arrange(&mut self, rect: Rect<i32>, scale: f64) {
+        self.set_rect(rect);
+    }
+    /// Measures the view and places it at the position with the measured size
+    fn layout_content(&mut self, x: i32, y: i32, width: i32, height: i32, typeface: &Typeface, scale: f64) -> Rect<i32> {
+        let (width, height) = self.measure(width, height, typeface, scale);
+        let rect = rect((x, y), (x + width, y + height));
+        self.arrange(rect, scale);
+        rect
+    }
     fn layout_in_rect(&mut self, rect: &Rect<i32>, scale: f64) {
         let (width, height) = self.get_content_size();
         let padding = self.get_padding(scale).scaled(scale);
@@ -50,6 +76,9 @@ pub trait View: Downcast {
     fn get_content_size(&self) -> (i32, i32);
     fn is_focused(&self) -> bool { false }
     fn is_break(&self) -> bool { false }
+    /// Checks if the user changes this property, so that bindings write it back to the model
+    #[allow(unused_variables)]
+    fn is_input(&self, name: &str) -> bool { false }
     #[allow(unused_variables)]
     fn set_focused(&self, focused: bool) {}
     fn set_focusable(&self, focusable: bool);
@@ -60,22 +89,46 @@ pub trait View: Downcast {
         let height = padding.top + height + padding.bottom;
         (width, height)
     }
+    /// Calculates the space, that the view can take for itself from the space that parent gives.
+    /// Views with `Min` dimensions can take all of it, the content decides their size in `measure()`.
     fn calculate_size(&mut self, width: i32, height: i32, scale: f64) -> (i32, i32) {
         let (b_width, b_height) = self.get_bounds();
         let margins = self.get_margin(scale);
         let width = match b_width {
-            Dimension::Min => width, // TODO change this after all children layout themselves
+            Dimension::Min => width,
             Dimension::Max => width - margins.left - margins.right,
             Dimension::Dip(dip) => (dip as f64 * scale).round() as i32,
             Dimension::Percent(p) => (width as f32 * p / 100f32).round() as i32
         };
         let height = match b_height {
-            Dimension::Min => height, // TODO change this after all children layout themselves
+            Dimension::Min => height,
             Dimension::Max => height - margins.top - margins.bottom,
             Dimension::Dip(dip) => (dip as f64 * scale).round() as i32,
             Dimension::Percent(p) => (height as f32 * p / 100f32).round() as i32
         };
-        (width, height)
+        match self.get_main() {
+            Some(main) => main.limit_size(width, height, scale),
+            None => (width, height)
+        }
+    }
+    /// Resolves dimensions of the view to its size in `measure()`.
+    /// The `content` is the size of content with padding, that `Min` dimensions take.
+    fn resolve_size(&mut self, width: i32, height: i32, content: (i32, i32), scale: f64) -> (i32, i32) {
+        let (b_width, b_height) = self.get_bounds();
+        let (width, height) = self.calculate_size(width, height, scale);
+        let width = match b_width {
+            Dimension::Min => content.0,
+            _ => width
+        };
+        let height = match b_height {
+            Dimension::Min => content.1,
+            _ => height
+        };
+        let (width, height) = match self.get_main() {
+            Some(main) => main.constrain_size(width, height, scale),
+            None => (width, height)
+        };
+        (width.max(0), height.max(0))
     }
     fn set_x(&mut self, x: i32) {
         let mut rect = self.get_rect();
@@ -91,6 +144,8 @@ pub trait View: Downcast {
     fn set_height(&mut self, height: Dimension);
     fn set_id(&mut self, id: &str);
     fn get_id(&self) -> String;
+    /// Gives access to main fields, to change styles of the view for example
+    fn get_main(&self) -> Option<RefMut<'_, FieldsMain>> { None }
     fn as_container(&self) -> Option<&dyn Container> { None }
     fn as_container_mut(&mut self) -> Option<&mut dyn Container> { None }
 
@@ -122,4 +177,5 @@ pub trait Container: View {
     fn add_view(&mut self, view: Element);
     fn get_view(&self, id: &str) -> Option<Element>;
     fn get_view_count(&self) -> usize;
+    fn get_views(&self) -> Vec<Element>;
 }
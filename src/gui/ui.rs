@@ -1,44 +1,313 @@
-use std::borrow::Cow;
+use std::any::{Any, TypeId};
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::fmt;
 use std::rc::Rc;
 
+use quick_xml::escape::escape;
 use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
 use speedy2d::dimen::Vector2;
 use speedy2d::window::{KeyScancode, ModifiersState, MouseButton, VirtualKeyCode};
 
-use super::containers::Frame;
-use super::themes::Theme;
-use super::traits::{Element, View};
+use super::assets::get_file;
+use super::common::{get_attributes, get_unescaped_attributes};
+use super::events::EventType;
+use super::containers::{Frame, Grid};
+use super::properties::{Property, to_markdown};
+use super::render::Renderer;
+use super::reload::LayoutWatcher;
+use super::model::{Model, parse_binding};
+use super::resources::Resources;
+use super::styles::{Stylesheet, StyleRule};
+use super::themes::{ClassicFactory, DarkFactory, StyledFactory, Theme, ThemeFactory, ThemeSpec};
+use super::traits::{Element, View, WeakElement};
 use super::types::Point;
 use super::themes::Typeface;
 
 use super::views::{Button, Edit, Label, CheckBox, List};
 
+/// Position in XML text, lines and columns start from 1
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Position {
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize
+}
+
+impl Position {
+    /// Finds the line and column of byte offset in the text
+    pub fn new(text: &str, offset: usize) -> Self {
+        let offset = offset.min(text.len());
+        let before = &text.as_bytes()[..offset];
+        let line_start = before.iter().rposition(|b| *b == b'\n').map(|p| p + 1).unwrap_or(0);
+        let line = before.iter().filter(|b| **b == b'\n').count() + 1;
+        let column = String::from_utf8_lossy(&before[line_start..]).chars().count() + 1;
+        Position { offset, line, column }
+    }
+
+    /// Finds the position of the tag, that ends right before the offset
+    pub fn of_tag(text: &str, end: usize) -> Self {
+        let end = end.min(text.len());
+        let start = text.as_bytes()[..end].iter().rposition(|b| *b == b'<').unwrap_or(end);
+        Position::new(text, start)
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// Problem that did not let to load the layout
+#[derive(Clone, Debug, PartialEq)]
+pub enum LoadError {
+    /// The text is not a well-formed XML
+    Syntax { position: Position, message: String },
+    /// There is no view type registered with this name
+    UnknownType { position: Position, tag: String },
+    /// The view can not contain other views, but there are some inside of it
+    NotContainer { position: Position, tag: String },
+    /// Closing tag is not the same as the opened one
+    Mismatch { position: Position, expected: String, found: String },
+    /// The view does not understand this attribute, it knows only attributes in `known`
+    UnknownAttribute { position: Position, tag: String, attribute: String, known: Vec<String> },
+    /// The value of attribute is wrong for this view
+    Attribute { position: Position, tag: String, attribute: String, message: String },
+    /// The error is in included file or template, its position is inside of that text
+    Fragment { position: Position, name: String, error: Box<LoadError> },
+    /// There is no handler registered with the name, that the attribute binds
+    UnknownHandler { position: Position, tag: String, attribute: String, handler: String },
+    /// The layout file could not be read
+    File { path: String, message: String },
+    /// There are no views in the layout
+    Empty
+}
+
+impl LoadError {
+    pub fn position(&self) -> Option<Position> {
+        match self {
+            LoadError::Syntax { position, .. } => Some(*position),
+            LoadError::UnknownType { position, .. } => Some(*position),
+            LoadError::NotContainer { position, .. } => Some(*position),
+            LoadError::Mismatch { position, .. } => Some(*position),
+            LoadError::UnknownAttribute { position, .. } => Some(*position),
+            LoadError::Attribute { position, .. } => Some(*position),
+            LoadError::Fragment { position, .. } => Some(*position),
+            LoadError::UnknownHandler { position, .. } => Some(*position),
+            LoadError::File { .. } | LoadError::Empty => None
+        }
+    }
+
+    fn position_mut(&mut self) -> Option<&mut Position> {
+        match self {
+            LoadError::Syntax { position, .. } => Some(position),
+            LoadError::UnknownType { position, .. } => Some(position),
+            LoadError::NotContainer { position, .. } => Some(position),
+            LoadError::Mismatch { position, .. } => Some(position),
+            LoadError::UnknownAttribute { position, .. } => Some(position),
+            LoadError::Attribute { position, .. } => Some(position),
+            LoadError::Fragment { position, .. } => Some(position),
+            LoadError::UnknownHandler { position, .. } => Some(position),
+            LoadError::File { .. } | LoadError::Empty => None
+        }
+    }
+
+    /// Moves the position of error in the part of text, that starts at `offset`, to the whole text
+    fn shift(mut self, text: &str, offset: usize) -> LoadError {
+        if let Some(position) = self.position_mut() {
+            *position = Position::new(text, offset + position.offset);
+        }
+        self
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Syntax { position, message } => write!(f, "{}: {}", position, message),
+            LoadError::UnknownType { position, tag } => write!(f, "{}: Unknown view type {}", position, tag),
+            LoadError::NotContainer { position, tag } => write!(f, "{}: {} can not contain other views", position, tag),
+            LoadError::Mismatch { position, expected, found } => write!(f, "{}: Expected </{}>, found </{}>", position, expected, found),
+            LoadError::UnknownAttribute { position, tag, attribute, known } => {
+                write!(f, "{}: Unknown attribute {} of {}, known are: {}", position, attribute, tag, known.join(", "))
+            }
+            LoadError::Attribute { position, tag, attribute, message } => {
+                write!(f, "{}: Attribute {} of {}: {}", position, attribute, tag, message)
+            }
+            LoadError::Fragment { position, name, error } => write!(f, "{}: In {}: {}", position, name, error),
+            LoadError::UnknownHandler { position, tag, attribute, handler } => {
+                write!(f, "{}: Handler {} for {} of {} is not registered", position, handler, attribute, tag)
+            }
+            LoadError::File { path, message } => write!(f, "Can not read {}: {}", path, message),
+            LoadError::Empty => write!(f, "There are no views in the layout")
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+/// Part of layout, that is written once in `<Template name="...">` and then used by its name as a tag.
+/// Attributes of the tag are put instead of `${name}` in the text, other attributes of `<Template>`
+/// give default values for them.
+#[derive(Clone, Debug)]
+struct Template {
+    text: String,
+    defaults: Vec<(String, String)>
+}
+
+impl Template {
+    fn expand(&self, name: &str, arguments: &[(String, String)]) -> Result<String, String> {
+        let mut result = String::new();
+        let mut rest = self.text.as_str();
+        while let Some(start) = rest.find("${") {
+            result.push_str(&rest[..start]);
+            let end = match rest[start..].find('}') {
+                None => return Err(String::from("Parameter is not closed with }")),
+                Some(end) => start + end
+            };
+            let parameter = &rest[start + 2..end];
+            let value = arguments.iter()
+                .chain(self.defaults.iter())
+                .find(|(n, _)| n == parameter)
+                .map(|(_, v)| v);
+            match value {
+                None => return Err(format!("Parameter {} of template {} is not given", parameter, name)),
+                // Values are unescaped, so they are escaped again, like in `to_xml()`
+                Some(value) => result.push_str(&escape(value))
+            }
+            rest = &rest[end + 1..];
+        }
+        result.push_str(rest);
+        Ok(result)
+    }
+}
+
+/// Styles, resources and strings that were added with methods of `UI`, not by the layout.
+/// They stay when the layout is loaded again, and the layout adds its own ones on top of them.
+#[derive(Default)]
+struct Additions {
+    stylesheet: Stylesheet,
+    resources: Resources,
+    locales: HashMap<String, Resources>,
+    /// Problems of loaded stylesheets, resources and themes
+    warnings: Vec<LoadError>
+}
+
+/// Attribute of view, that references a string, to set it again when locale changes
+struct Localized {
+    view: WeakElement,
+    attribute: String,
+    reference: String
+}
+
+/// Function that views call on events
+pub type EventListener = Box<dyn FnMut(&mut UI, &dyn View) -> bool>;
+type Listener = Rc<RefCell<EventListener>>;
+
+/// Attribute of view, that is bound to the value of model
+struct Binding {
+    view: WeakElement,
+    attribute: String,
+    key: String,
+    /// The user changes this attribute, so it is written back to the model
+    input: bool,
+    /// Value that the view and the model had after the last synchronization
+    last: Option<String>
+}
+
+/// Function that is called with new value, when the value of model changes
+pub type Observer = Box<dyn FnMut(&mut UI, &str)>;
+
+/// Includes and templates can not be nested deeper than this, to stop on cycles
+const MAX_FRAGMENT_DEPTH: usize = 16;
+
 pub struct UI {
     width: u32,
     height: u32,
+    scale: f64,
     typeface: Typeface,
     root: Option<Element>,
     types: HashMap<String, fn() -> Element>,
+    properties: HashMap<String, Vec<Property>>,
+    type_names: HashMap<TypeId, String>,
+    stylesheet: Stylesheet,
+    resources: Resources,
+    locales: HashMap<String, Resources>,
+    locale: String,
+    localized: Vec<Localized>,
+    /// Localized texts need to be set again, when views are not busy with events
+    relocalize: bool,
+    /// Listeners by ids of views, they are set again to new views after reloading the layout
+    listeners: Vec<(String, EventType, Listener)>,
+    /// Listeners by names, for attributes like `on_click="save"` in layouts
+    handlers: HashMap<String, Listener>,
+    /// Views with attributes, that bind handlers, to write them back
+    handled: Vec<(WeakElement, String, String)>,
+    model: Model,
+    bindings: Vec<Binding>,
+    observers: Vec<(String, Observer)>,
+    watcher: Option<LayoutWatcher>,
+    templates: HashMap<String, Template>,
+    /// Includes and templates, that are being parsed, to find the ones that include themselves
+    fragments: Vec<String>,
+    warnings: Vec<LoadError>,
+    additions: Additions,
+    themes: HashMap<String, Rc<dyn ThemeFactory>>,
+    theme: String,
+    repaint: bool,
     on_start: Option<Box<dyn FnMut(&mut UI)>>
 }
 
 #[allow(dead_code)]
 impl UI {
     pub fn new(width: u32, height: u32, typeface: Typeface) -> Self {
-        let mut ui = UI { width, height, typeface, root: None, types: HashMap::new(), on_start: None };
+        let mut ui = UI {
+            width,
+            height,
+            scale: 1.0,
+            typeface,
+            root: None,
+            types: HashMap::new(),
+            properties: HashMap::new(),
+            type_names: HashMap::new(),
+            stylesheet: Stylesheet::new(),
+            resources: Resources::new(),
+            locales: HashMap::new(),
+            locale: String::new(),
+            localized: Vec::new(),
+            relocalize: false,
+            listeners: Vec::new(),
+            handlers: HashMap::new(),
+            handled: Vec::new(),
+            model: Model::new(),
+            bindings: Vec::new(),
+            observers: Vec::new(),
+            watcher: None,
+            templates: HashMap::new(),
+            fragments: Vec::new(),
+            warnings: Vec::new(),
+            additions: Additions::default(),
+            themes: HashMap::new(),
+            theme: String::from("Classic"),
+            repaint: false,
+            on_start: None
+        };
         ui.register::<Label>("Label");
         ui.register::<Button>("Button");
         ui.register::<CheckBox>("CheckBox");
         ui.register::<Edit>("Edit");
         ui.register::<List>("List");
         ui.register::<Frame>("Frame");
+        ui.register::<Grid>("Grid");
+        ui.register_theme("Classic", ClassicFactory);
+        ui.register_theme("Dark", DarkFactory);
         ui
     }
 
     pub fn add_view(&mut self, view: Element) {
+        self.apply_styles_to(&view);
         match &self.root {
             None => {
                 self.root = Some(view);
@@ -68,10 +337,392 @@ impl UI {
 
     pub fn register<T: Default + View + 'static>(&mut self, name: &str) {
         self.types.insert(name.to_owned(), || Rc::new(RefCell::from(T::default())));
+        self.type_names.insert(TypeId::of::<T>(), name.to_owned());
+        self.properties.insert(name.to_owned(), T::properties());
+    }
+
+    /// Gets names of all registered types, sorted
+    pub fn get_type_names(&self) -> Vec<&str> {
+        let mut names = self.types.keys().map(|n| n.as_str()).collect::<Vec<_>>();
+        names.sort_unstable();
+        names
+    }
+
+    /// Gets properties, that the registered type understands
+    pub fn get_properties(&self, type_name: &str) -> Option<&[Property]> {
+        self.properties.get(type_name).map(|p| p.as_slice())
+    }
+
+    /// Gets names of attributes, that the registered type understands
+    pub fn get_attributes(&self, type_name: &str) -> Option<Vec<&'static str>> {
+        self.get_properties(type_name).map(|p| p.iter().map(|p| p.name).collect())
     }
 
+    /// Makes Markdown reference of all registered types and their properties
+    pub fn document(&self) -> String {
+        let mut result = String::new();
+        for name in self.get_type_names() {
+            result.push_str(&format!("## {}\n\n", name));
+            match self.get_properties(name) {
+                Some(properties) if !properties.is_empty() => result.push_str(&to_markdown(properties)),
+                _ => result.push_str("Properties are not described.\n")
+            }
+            result.push('\n');
+        }
+        result
+    }
+
+    /// Problems, that were skipped while loading the layout, stylesheets, resources and themes
+    pub fn get_warnings(&self) -> &[LoadError] {
+        &self.warnings
+    }
+
+    /// Gets the name that the type of this view is registered with
+    pub fn get_type_name(&self, view: &dyn View) -> Option<&str> {
+        self.type_names.get(&Any::type_id(view.as_any())).map(|n| n.as_str())
+    }
+
+    /// Creates the view of registered type, panics if there is no such type
     pub fn create(&self, name: &str) -> Element {
-        self.types.get(name).expect("No type!")()
+        self.try_create(name).unwrap_or_else(|| panic!("Type {} is not registered", name))
+    }
+
+    /// Creates the view of registered type, or returns None if there is no such type
+    pub fn try_create(&self, name: &str) -> Option<Element> {
+        self.types.get(name).map(|constructor| constructor())
+    }
+
+    pub fn register_theme<F: ThemeFactory + 'static>(&mut self, name: &str, factory: F) {
+        self.themes.insert(name.to_owned(), Rc::new(factory));
+    }
+
+    /// Loads the theme description with the assets provider, and registers it by the name from that file.
+    /// Returns that name, wrong parts of the theme are skipped and kept in `get_warnings()`.
+    pub fn load_theme(&mut self, path: &str) -> Result<String, LoadError> {
+        let mut warnings = Vec::new();
+        let spec = ThemeSpec::load(path, &mut warnings)?;
+        self.add_warnings(warnings);
+        let name = spec.name.clone();
+        self.register_theme(&name, StyledFactory::new(spec));
+        Ok(name)
+    }
+
+    /// Makes the theme with given name active, the next frame will be painted with it.
+    /// Views are laid out again with the typeface of that theme.
+    /// Returns false if there is no such theme.
+    pub fn set_theme(&mut self, name: &str) -> bool {
+        let factory = match self.themes.get(name) {
+            None => return false,
+            Some(factory) => Rc::clone(factory)
+        };
+        if self.theme != name {
+            self.theme = name.to_owned();
+            self.set_typeface(factory.typeface());
+            self.repaint = true;
+        }
+        true
+    }
+
+    pub fn get_theme(&self) -> &str {
+        &self.theme
+    }
+
+    /// Creates active theme for one frame, that will draw with given renderer
+    pub fn create_theme<'r>(&self, renderer: &'r mut dyn Renderer, width: i32, height: i32, scale: f64) -> Box<dyn Theme + 'r> {
+        self.themes.get(&self.theme).expect("No theme!").create(renderer, width, height, scale)
+    }
+
+    /// Adds rules of the stylesheet after the current ones, and applies them to all views
+    /// They stay when the layout is loaded again, and styles of the layout are added after them.
+    pub fn add_stylesheet(&mut self, stylesheet: Stylesheet) {
+        self.additions.stylesheet.append(stylesheet.clone());
+        self.stylesheet.append(stylesheet);
+        self.apply_styles();
+    }
+
+    /// Loads the stylesheet with the assets provider and adds it.
+    /// Wrong rules are skipped and kept in `get_warnings()`.
+    pub fn load_stylesheet(&mut self, path: &str) -> Result<(), LoadError> {
+        let mut warnings = Vec::new();
+        let stylesheet = Stylesheet::load(path, &mut warnings)?;
+        self.add_warnings(warnings);
+        self.add_stylesheet(stylesheet);
+        Ok(())
+    }
+
+    pub fn get_stylesheet(&self) -> &Stylesheet {
+        &self.stylesheet
+    }
+
+    /// Adds named values like the ones from `<Resources>` of layout, replacing values with the same names.
+    /// They stay when the layout is loaded again, and values of the layout replace them.
+    pub fn add_resources(&mut self, resources: Resources) {
+        self.additions.resources.append(resources.clone());
+        self.resources.append(resources);
+    }
+
+    /// Loads resources with the assets provider and adds them.
+    /// Wrong values are skipped and kept in `get_warnings()`.
+    pub fn load_resources(&mut self, path: &str) -> Result<(), LoadError> {
+        let mut warnings = Vec::new();
+        let resources = Resources::load(path, &mut warnings)?;
+        self.add_warnings(warnings);
+        self.add_resources(resources);
+        Ok(())
+    }
+
+    pub fn get_resources(&self) -> &Resources {
+        &self.resources
+    }
+
+    /// Adds strings for the locale, replacing its strings with the same names.
+    /// They stay when the layout is loaded again, and strings of the layout replace them.
+    pub fn add_locale(&mut self, locale: &str, resources: Resources) {
+        self.additions.locales.entry(locale.to_owned()).or_default().append(resources.clone());
+        self.locales.entry(locale.to_owned()).or_default().append(resources);
+    }
+
+    /// Loads strings for the locale with the assets provider and adds them.
+    /// Wrong values are skipped and kept in `get_warnings()`.
+    pub fn load_locale(&mut self, locale: &str, path: &str) -> Result<(), LoadError> {
+        let mut warnings = Vec::new();
+        let resources = Resources::load(path, &mut warnings)?;
+        self.add_warnings(warnings);
+        self.add_locale(locale, resources);
+        Ok(())
+    }
+
+    /// Keeps problems of loaded files, they stay when the layout is loaded again
+    fn add_warnings(&mut self, warnings: Vec<LoadError>) {
+        self.additions.warnings.extend(warnings.iter().cloned());
+        self.warnings.extend(warnings);
+    }
+
+    /// Gets names of locales, that have strings, sorted
+    pub fn get_locales(&self) -> Vec<&str> {
+        let mut locales = self.locales.keys().map(|l| l.as_str()).collect::<Vec<_>>();
+        locales.sort_unstable();
+        locales
+    }
+
+    pub fn get_locale(&self) -> &str {
+        &self.locale
+    }
+
+    /// Switches to strings of another locale, sets them again to all views that reference them,
+    /// and lays the views out, as texts change their sizes.
+    /// Strings that the locale does not have are taken from common resources.
+    /// When it is called from event listeners, texts change right after the event.
+    pub fn set_locale(&mut self, locale: &str) {
+        self.locale = locale.to_owned();
+        self.relocalize = true;
+        self.repaint = true;
+        self.relocalize_views();
+    }
+
+    fn relocalize_views(&mut self) {
+        let busy = self.root.as_ref().map(|r| r.try_borrow_mut().is_err()).unwrap_or(false);
+        if !self.relocalize || busy {
+            return;
+        }
+        self.relocalize = false;
+        self.localized.retain(|l| l.view.strong_count() > 0);
+        for localized in &self.localized {
+            let value = match self.resolve(&localized.reference) {
+                Ok(value) => value,
+                Err(message) => {
+                    println!("Attribute {}: {}", localized.attribute, message);
+                    continue;
+                }
+            };
+            if let Some(view) = localized.view.upgrade() {
+                if let Err(message) = view.borrow_mut().set_any(&localized.attribute, &value) {
+                    println!("Attribute {}: {}", localized.attribute, message);
+                }
+            }
+        }
+        if self.width > 0 && self.height > 0 {
+            self.layout(self.width, self.height, self.scale);
+        }
+    }
+
+    /// Gets the string of current locale, or from common resources
+    pub fn get_string(&self, name: &str) -> Option<String> {
+        self.locales.get(&self.locale)
+            .and_then(|l| l.get("string", name))
+            .or_else(|| self.resources.get("string", name))
+            .map(|s| s.to_owned())
+    }
+
+    /// Sets the attribute of view to the string, that is referenced like `@string/ok`,
+    /// and remembers it to change the value with locale.
+    /// When it is called from event listeners, the text changes right after the event.
+    pub fn set_localized(&mut self, view: &Element, attribute: &str, reference: &str) -> Result<(), String> {
+        if !reference.starts_with("@string/") {
+            return Err(format!("Wrong reference {:?}, expected @string/name", reference));
+        }
+        self.resolve(reference)?;
+        self.localized.retain(|l| !(l.view.ptr_eq(&Rc::downgrade(view)) && l.attribute == attribute));
+        self.localized.push(Localized { view: Rc::downgrade(view), attribute: attribute.to_owned(), reference: reference.to_owned() });
+        self.relocalize = true;
+        self.repaint = true;
+        self.relocalize_views();
+        Ok(())
+    }
+
+    /// Gets the reference to string, that the attribute of view was set with
+    fn get_localized(&self, view: &dyn View, attribute: &str) -> Option<&str> {
+        self.localized.iter()
+            .filter(|l| l.attribute == attribute)
+            .find(|l| is_same_view(&l.view, view))
+            .map(|l| l.reference.as_str())
+    }
+
+    /// Gets the value, that the attribute references, strings of current locale go first
+    fn resolve(&self, value: &str) -> Result<String, String> {
+        if let Some(name) = value.strip_prefix("@string/") {
+            if let Some(string) = self.locales.get(&self.locale).and_then(|l| l.get("string", name)) {
+                return Ok(string.to_owned());
+            }
+        }
+        self.resources.resolve(value)
+    }
+
+    /// Applies the stylesheet to all views again, needed after changing ids or classes of views
+    pub fn apply_styles(&mut self) {
+        if let Some(root) = self.root.clone() {
+            self.apply_styles_to(&root);
+        }
+        if self.width > 0 && self.height > 0 {
+            self.layout(self.width, self.height, self.scale);
+        }
+        self.repaint = true;
+    }
+
+    fn apply_styles_to(&self, view: &Element) {
+        let view = view.borrow();
+        if let Some(mut main) = view.get_main() {
+            let view_type = self.get_type_name(&*view).unwrap_or_default();
+            self.stylesheet.apply(view_type, &mut main);
+        }
+        if let Some(container) = view.as_container() {
+            for child in container.get_views() {
+                self.apply_styles_to(&child);
+            }
+        }
+    }
+
+    /// Views need layout after their state changes, if fonts of styles depend on state
+    fn on_state_changed(&mut self, changed: bool) -> bool {
+        let synced = self.sync_bindings();
+        if self.relocalize {
+            self.relocalize_views();
+            return true;
+        }
+        if changed && self.stylesheet.has_state_fonts() {
+            self.layout(self.width, self.height, self.scale);
+        }
+        changed || synced
+    }
+
+    pub fn get_model(&self) -> &Model {
+        &self.model
+    }
+
+    pub fn get_value(&self, key: &str) -> Option<&str> {
+        self.model.get(key)
+    }
+
+    /// Sets the value of model, views that are bound to it change right away,
+    /// or right after the event, when it is called from event listeners.
+    pub fn set_value(&mut self, key: &str, value: &str) {
+        if self.model.set(key, value) {
+            self.sync_bindings();
+        }
+    }
+
+    /// Calls the function every time the value of model changes, from code or by the user
+    pub fn observe(&mut self, key: &str, func: Observer) {
+        self.observers.push((key.to_owned(), func));
+    }
+
+    /// Writes values, that the user changed, to the model, and then values of model to views.
+    /// Returns true if some views were changed.
+    fn sync_bindings(&mut self) -> bool {
+        let busy = self.root.as_ref().map(|r| r.try_borrow_mut().is_err()).unwrap_or(false);
+        if busy {
+            return false;
+        }
+        self.bindings.retain(|b| b.view.strong_count() > 0);
+        for binding in self.bindings.iter_mut().filter(|b| b.input && b.last.is_some()) {
+            let value = binding.view.upgrade().and_then(|v| v.borrow().get_any(&binding.attribute));
+            if let Some(value) = value {
+                if binding.last.as_ref() != Some(&value) {
+                    self.model.set(&binding.key, &value);
+                    binding.last = Some(value);
+                }
+            }
+        }
+        let mut changed = false;
+        for binding in self.bindings.iter_mut() {
+            let view = match binding.view.upgrade() {
+                None => continue,
+                Some(view) => view
+            };
+            match self.model.get(&binding.key) {
+                Some(value) if binding.last.as_deref() != Some(value) => {
+                    if let Err(message) = view.borrow_mut().set_any(&binding.attribute, value) {
+                        println!("Binding {} to {}: {}", binding.attribute, binding.key, message);
+                    }
+                    binding.last = Some(value.to_owned());
+                    changed = true;
+                }
+                Some(_) => {}
+                None => {
+                    // The model does not have it yet, so it starts with the value of view
+                    let value = view.borrow().get_any(&binding.attribute).unwrap_or_default();
+                    self.model.set(&binding.key, &value);
+                    binding.last = Some(value);
+                }
+            }
+        }
+        self.notify_observers();
+        if changed {
+            if self.width > 0 && self.height > 0 {
+                self.layout(self.width, self.height, self.scale);
+            }
+            self.repaint = true;
+        }
+        changed
+    }
+
+    fn notify_observers(&mut self) {
+        let keys = self.model.take_changed();
+        if keys.is_empty() || self.observers.is_empty() {
+            return;
+        }
+        let mut observers = std::mem::take(&mut self.observers);
+        for key in keys {
+            let value = self.model.get(&key).unwrap_or_default().to_owned();
+            for (_, func) in observers.iter_mut().filter(|(k, _)| *k == key) {
+                func(self, &value);
+            }
+        }
+        // Observers can add new observers
+        observers.append(&mut self.observers);
+        self.observers = observers;
+    }
+
+    /// Sets default typeface for all views, and lays them out again
+    pub fn set_typeface(&mut self, typeface: Typeface) {
+        self.typeface = typeface;
+        if self.width > 0 && self.height > 0 {
+            self.layout(self.width, self.height, self.scale);
+        }
+    }
+
+    pub fn get_typeface(&self) -> &Typeface {
+        &self.typeface
     }
 
     pub fn on_start(&mut self, func: Box<dyn FnMut(&mut UI)>) {
@@ -81,6 +732,7 @@ impl UI {
     pub fn layout(&mut self, width: u32, height: u32, scale: f64) {
         self.width = width;
         self.height = height;
+        self.scale = scale;
         let root = self.root.clone();
         if let Some(root) = root {
             root.borrow_mut().layout_content(0, 0, width as i32, height as i32, &self.typeface.clone(), scale);
@@ -94,11 +746,17 @@ impl UI {
     }
 
     pub fn update(&mut self) -> bool {
+        if self.watcher.as_mut().map(|w| w.poll()).unwrap_or(false) {
+            self.reload_layout();
+        }
+        self.sync_bindings();
+        let repaint = self.repaint;
+        self.repaint = false;
         let root = self.root.clone();
         if let Some(root) = root {
-            return root.borrow_mut().update(self);
+            return root.borrow_mut().update(self) || repaint;
         }
-        false
+        repaint
     }
 
     pub fn paint(&self, theme: &mut dyn Theme) {
@@ -106,83 +764,560 @@ impl UI {
         if let Some(root) = &self.root {
             root.borrow().paint(Point::from((0, 0)), theme);
         }
+        if let Some(watcher) = &self.watcher {
+            watcher.paint(theme, self.width, self.scale);
+        }
     }
 
-    pub fn from_xml(xml: &str, width: u32, height: u32, typeface: Typeface) -> Option<Self> {
+    /// Sets the listener to the view with this id, like `View::on_event()`.
+    /// The listener stays with the id, so it is set again to the new view after reloading the layout.
+    /// Returns false if there is no such view now.
+    pub fn on_event(&mut self, id: &str, event: EventType, func: EventListener) -> bool {
+        self.listeners.retain(|(i, e, _)| !(i == id && *e == event));
+        self.listeners.push((id.to_owned(), event, Rc::new(RefCell::new(func))));
+        self.attach_listener(self.listeners.len() - 1)
+    }
+
+    fn attach_listener(&self, index: usize) -> bool {
+        let (id, event, listener) = &self.listeners[index];
+        match self.get_view(id) {
+            None => false,
+            Some(view) => {
+                let listener = Rc::clone(listener);
+                view.borrow_mut().on_event(*event, Box::new(move |ui, view| (listener.borrow_mut())(ui, view)));
+                true
+            }
+        }
+    }
+
+    /// Registers the listener by name, so that layouts can bind it like `on_click="save"`.
+    /// Handlers must be registered before loading the layout with `set_layout()` or `load_layout()`.
+    pub fn register_handler(&mut self, name: &str, func: EventListener) {
+        self.handlers.insert(name.to_owned(), Rc::new(RefCell::new(func)));
+    }
+
+    pub fn has_handler(&self, name: &str) -> bool {
+        self.handlers.contains_key(name)
+    }
+
+    /// Loads the layout from the file on disk, like `set_layout()`
+    pub fn load_layout(&mut self, path: &str) -> Result<(), LoadError> {
+        let xml = std::fs::read_to_string(path).map_err(|e| LoadError::File { path: path.to_owned(), message: e.to_string() })?;
+        self.set_layout(&xml)
+    }
+
+    /// Loads the layout from XML, instead of current views and their styles, resources and templates.
+    /// Registered types, handlers, themes and listeners set with `UI::on_event()` stay,
+    /// so this is the way to load layouts with own types or named handlers.
+    /// Styles, resources and strings that were added with methods like `load_locale()` stay too,
+    /// and the ones of the layout are added on top of them.
+    pub fn set_layout(&mut self, xml: &str) -> Result<(), LoadError> {
+        let mut loaded = UI::new(self.width, self.height, self.typeface.clone());
+        loaded.types = self.types.clone();
+        loaded.type_names = self.type_names.clone();
+        loaded.properties = self.properties.clone();
+        loaded.handlers = self.handlers.clone();
+        loaded.stylesheet = self.additions.stylesheet.clone();
+        loaded.resources = self.additions.resources.clone();
+        loaded.locales = self.additions.locales.clone();
+        loaded.locale = self.locale.clone();
+        loaded.warnings = self.additions.warnings.clone();
+        let mut stack: Vec<Element> = Vec::new();
+        UI::parse_xml(&mut loaded, xml, &mut stack, false, 0)?;
+        if loaded.root.is_none() {
+            return Err(LoadError::Empty);
+        }
+        self.root = loaded.root;
+        self.stylesheet = loaded.stylesheet;
+        self.resources = loaded.resources;
+        self.locales = loaded.locales;
+        self.templates = loaded.templates;
+        self.localized = loaded.localized;
+        self.handled = loaded.handled;
+        self.bindings = loaded.bindings;
+        self.warnings = loaded.warnings;
+        for index in 0..self.listeners.len() {
+            self.attach_listener(index);
+        }
+        self.relocalize = true;
+        self.relocalize_views();
+        self.sync_bindings();
+        self.apply_styles();
+        Ok(())
+    }
+
+    /// Loads the layout from the file, and loads it again every time the file changes.
+    /// Load errors are shown over the views, instead of failing, so this is for development.
+    pub fn watch_layout(&mut self, path: &str) {
+        self.watcher = Some(LayoutWatcher::new(path));
+        self.reload_layout();
+    }
+
+    fn reload_layout(&mut self) {
+        let path = match &self.watcher {
+            None => return,
+            Some(watcher) => watcher.get_path().to_owned()
+        };
+        let message = match self.load_layout(&path) {
+            Ok(_) => None,
+            Err(e) => {
+                println!("Error loading {}: {}", path, e);
+                Some(format!("{}: {}", path, e))
+            }
+        };
+        let (typeface, width, scale) = (self.typeface.clone(), self.width, self.scale);
+        if let Some(watcher) = self.watcher.as_mut() {
+            watcher.set_error(message.as_deref(), &typeface, width, scale);
+        }
+        self.repaint = true;
+    }
+
+    /// Loads the layout from XML, the error tells what and where is wrong in it.
+    /// Attributes that views do not understand, wrong styles and resources are skipped, and kept in `get_warnings()`.
+    /// Other layouts can be put in with `<Include src="path"/>` through the assets provider,
+    /// and templates are expanded where their names are used, after they are defined:
+    /// ```xml
+    /// <Template name="Field" label="Name">
+    ///     <Frame><Label text="${label}"/><Edit id="${id}"/></Frame>
+    /// </Template>
+    /// <Field id="email" label="E-mail"/>
+    /// ```
+    /// Attributes can reference values of `<Resources>` like `text="@string/ok"`, see `Resources`.
+    /// Strings of `<Resources locale="sk">` replace common ones after `set_locale("sk")`.
+    /// Attributes like `on_click="save"` bind handlers, that must be registered before loading
+    /// with `register_handler()`, so such layouts are loaded with `set_layout()`.
+    pub fn from_xml(xml: &str, width: u32, height: u32, typeface: Typeface) -> Result<Self, LoadError> {
+        UI::load_xml(xml, width, height, typeface, false)
+    }
+
+    /// Loads the layout from XML like `from_xml()`, but fails on the first problem that would be a warning
+    pub fn from_xml_strict(xml: &str, width: u32, height: u32, typeface: Typeface) -> Result<Self, LoadError> {
+        UI::load_xml(xml, width, height, typeface, true)
+    }
+
+    fn load_xml(xml: &str, width: u32, height: u32, typeface: Typeface, strict: bool) -> Result<Self, LoadError> {
         let mut ui = UI::new(width, height, typeface);
+        let mut stack: Vec<Element> = Vec::new();
+        UI::parse_xml(&mut ui, xml, &mut stack, strict, 0)?;
+        if ui.root.is_none() {
+            return Err(LoadError::Empty);
+        }
+        ui.sync_bindings();
+        ui.apply_styles();
+        Ok(ui)
+    }
+
+    /// Parses the layout or its fragment, views are added to the last view of the stack.
+    /// Fragments from `<Include src="...">` and templates are parsed with greater depth.
+    fn parse_xml(ui: &mut UI, xml: &str, stack: &mut Vec<Element>, strict: bool, depth: usize) -> Result<(), LoadError> {
         let mut reader = Reader::from_str(xml);
         reader.trim_text(true);
+        // Closing tags are checked here, to report them with positions
+        reader.check_end_names(false);
 
         let mut txt = Vec::new();
-        let mut stack: Vec<Element> = Vec::new();
+        let mut tags: Vec<String> = Vec::new();
+        let mut styles_depth = 0;
 
         loop {
-            match reader.read_event() {
-                Ok(Event::Start(ref e)) => {
-                    let element = UI::parse_element(&mut ui, e);
-                    stack.push(element);
+            let event = reader.read_event().map_err(|e| {
+                LoadError::Syntax { position: Position::new(xml, reader.buffer_position()), message: e.to_string() }
+            })?;
+            let position = Position::of_tag(xml, reader.buffer_position());
+            match &event {
+                Event::Start(e) => tags.push(String::from_utf8_lossy(e.name().as_ref()).to_string()),
+                Event::End(e) => {
+                    let found = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                    match tags.pop() {
+                        None => return Err(LoadError::Syntax { position, message: format!("Closing tag {} without opening one", found) }),
+                        Some(expected) if expected != found => return Err(LoadError::Mismatch { position, expected, found }),
+                        Some(_) => {}
+                    }
+                }
+                _ => {}
+            }
+            match event {
+                Event::Start(ref e) | Event::Empty(ref e) if styles_depth > 0 && !UI::is_style(e) => {
+                    let tag = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                    return Err(LoadError::Syntax { position, message: format!("{} can not be inside of styles, only Style can", tag) });
                 },
-                Ok(Event::Empty(ref e)) => {
-                    let element = UI::parse_element(&mut ui, e);
-                    let parent = stack.pop().unwrap();
-                    {
-                        element.borrow_mut().set_parent(Some(Rc::downgrade(&parent)));
-                        let mut ref_mut = parent.borrow_mut();
-                        let container = ref_mut.as_container_mut().unwrap();
-                        container.add_view(element);
+                Event::Start(ref e) if e.name().as_ref() == b"Template" => {
+                    let span = reader.read_to_end(e.name()).map_err(|e| LoadError::Syntax { position, message: e.to_string() })?;
+                    tags.pop();
+                    UI::parse_template(ui, e, &xml[span], position)?;
+                },
+                Event::Start(ref e) if e.name().as_ref() == b"Resources" => {
+                    reader.read_to_end(e.name()).map_err(|e| LoadError::Syntax { position, message: e.to_string() })?;
+                    tags.pop();
+                    let mut warnings = Vec::new();
+                    let resources = Resources::from_xml(&xml[position.offset..reader.buffer_position()], &mut warnings)
+                        .map_err(|error| error.shift(xml, position.offset))?;
+                    for warning in warnings {
+                        UI::warn(ui, warning.shift(xml, position.offset), strict)?;
                     }
-                    stack.push(parent);
+                    UI::add_resources_for(ui, e, resources);
                 },
-                Ok(Event::End(_)) => {
-                    // TODO check that it is the same tag
-                    let element = stack.pop().unwrap();
-                    match stack.pop() {
-                        None => {
-                            ui.add_view(element);
-                        }
-                        Some(parent) => {
-                            {
-                                let mut ref_mut = parent.borrow_mut();
-                                let container = ref_mut.as_container_mut().unwrap();
-                                container.add_view(element);
-                            }
-                            stack.push(parent);
+                Event::Empty(ref e) if e.name().as_ref() == b"Resources" => {
+                    let attributes = get_attributes(e);
+                    if let Some((_, path)) = attributes.iter().find(|(name, _)| name == "src") {
+                        let mut warnings = Vec::new();
+                        let result = Resources::load(path, &mut warnings);
+                        UI::warn_file(ui, path, warnings, position, strict)?;
+                        match result {
+                            Ok(resources) => UI::add_resources_for(ui, e, resources),
+                            Err(error) => UI::warn_file(ui, path, vec![error], position, strict)?
                         }
                     }
                 },
+                Event::Start(ref e) if UI::is_style(e) => {
+                    UI::parse_style(ui, e, position, strict)?;
+                    styles_depth += 1;
+                },
+                Event::Empty(ref e) if UI::is_style(e) => {
+                    UI::parse_style(ui, e, position, strict)?;
+                },
+                Event::End(_) if styles_depth > 0 => {
+                    styles_depth -= 1;
+                },
+                Event::Empty(ref e) if UI::is_fragment(ui, e) => {
+                    UI::parse_fragment(ui, e, stack, position, strict, depth)?;
+                },
+                Event::Start(ref e) if UI::is_fragment(ui, e) => {
+                    let span = reader.read_to_end(e.name()).map_err(|e| LoadError::Syntax { position, message: e.to_string() })?;
+                    tags.pop();
+                    if !xml[span].trim().is_empty() {
+                        return Err(LoadError::Syntax { position, message: String::from("Include and templates can not have views inside") });
+                    }
+                    UI::parse_fragment(ui, e, stack, position, strict, depth)?;
+                },
+                Event::Start(ref e) => {
+                    UI::check_root(ui, stack, position)?;
+                    let element = UI::parse_element(ui, e, position, strict)?;
+                    stack.push(element);
+                },
+                Event::Empty(ref e) => {
+                    let element = UI::parse_element(ui, e, position, strict)?;
+                    UI::add_element(ui, stack, element, position)?;
+                },
+                Event::End(_) => {
+                    // Tags are balanced, so it is always there
+                    let element = stack.pop().unwrap();
+                    UI::add_element(ui, stack, element, position)?;
+                },
                 // unescape and decode the text event using the reader encoding
-                Ok(Event::Text(e)) => txt.push(e.unescape().unwrap()),
-                Ok(Event::Eof) => break, // exits the loop when reaching end of file
-                Err(e) => panic!("Error at position {}: {:?}", reader.buffer_position(), e),
+                Event::Text(e) => {
+                    let text = e.unescape().map_err(|e| LoadError::Syntax { position, message: e.to_string() })?;
+                    txt.push(text);
+                },
+                Event::Eof => break, // exits the loop when reaching end of file
                 _ => (), // There are several other `Event`s we do not consider here
             }
         }
-        Some(ui)
+        if let Some(tag) = tags.last() {
+            let position = Position::new(xml, xml.len());
+            return Err(LoadError::Syntax { position, message: format!("Tag {} is not closed", tag) });
+        }
+        Ok(())
     }
 
-    fn parse_element(ui: &mut UI, e: &BytesStart) -> Element {
-        let attributes = e
-            .attributes()
-            .map(|a| a.unwrap())
-            .collect::<Vec<_>>();
-        //println!("attributes values: {:?}", attributes);
-        let view_type = String::from_utf8(e.name().0.to_vec()).unwrap();
-        let view = ui.create(&view_type);
-        //println!("Loaded {}", &view_type);
-        for attribute in attributes {
-            let name = String::from_utf8(attribute.key.0.to_vec()).unwrap();
-            let value = match attribute.value {
-                Cow::Borrowed(c) => {
-                    String::from_utf8(c.to_vec()).unwrap()
+    /// Adds resources of `<Resources>` to common ones, or to strings of locale from its `locale` attribute
+    fn add_resources_for(ui: &mut UI, e: &BytesStart, resources: Resources) {
+        match get_attributes(e).into_iter().find(|(name, _)| name == "locale") {
+            None => ui.add_resources(resources),
+            Some((_, locale)) => ui.add_locale(&locale, resources)
+        }
+    }
+
+    /// Remembers the `<Template>` to expand it later, when its name is used as a tag
+    fn parse_template(ui: &mut UI, e: &BytesStart, text: &str, position: Position) -> Result<(), LoadError> {
+        let mut defaults = get_unescaped_attributes(e);
+        let name = match defaults.iter().position(|(n, _)| n == "name") {
+            None => return Err(LoadError::Attribute { position, tag: String::from("Template"), attribute: String::from("name"), message: String::from("Template must have a name") }),
+            Some(index) => defaults.remove(index).1
+        };
+        if ui.types.contains_key(&name) || matches!(name.as_str(), "Include" | "Template" | "Style" | "Stylesheet") {
+            let message = format!("Name {} is already taken", name);
+            return Err(LoadError::Attribute { position, tag: String::from("Template"), attribute: String::from("name"), message });
+        }
+        ui.templates.insert(name, Template { text: text.to_owned(), defaults });
+        Ok(())
+    }
+
+    fn is_fragment(ui: &UI, e: &BytesStart) -> bool {
+        let name = e.name();
+        name.as_ref() == b"Include" || ui.templates.contains_key(String::from_utf8_lossy(name.as_ref()).as_ref())
+    }
+
+    /// Parses the file of `<Include src="...">` or expands the template, in place of this tag
+    fn parse_fragment(ui: &mut UI, e: &BytesStart, stack: &mut Vec<Element>, position: Position, strict: bool, depth: usize) -> Result<(), LoadError> {
+        let tag = String::from_utf8_lossy(e.name().as_ref()).to_string();
+        let attributes = get_unescaped_attributes(e);
+        let attribute_error = |attribute: &str, message: String| {
+            LoadError::Attribute { position, tag: tag.clone(), attribute: attribute.to_owned(), message }
+        };
+        if depth >= MAX_FRAGMENT_DEPTH {
+            return Err(LoadError::Syntax { position, message: format!("Includes and templates are nested deeper than {}", MAX_FRAGMENT_DEPTH) });
+        }
+        let (name, text) = if tag == "Include" {
+            let src = match attributes.iter().find(|(n, _)| n == "src") {
+                None => return Err(attribute_error("src", String::from("Include must have a src"))),
+                Some((_, src)) => src.clone()
+            };
+            let name = format!("file {}", src);
+            if ui.fragments.contains(&name) {
+                return Err(attribute_error("src", format!("File {} includes itself", src)));
+            }
+            let bytes = get_file(&src).ok_or_else(|| attribute_error("src", format!("File {} is not found", src)))?;
+            let text = String::from_utf8(bytes).map_err(|e| attribute_error("src", format!("File {} is not UTF-8: {}", src, e)))?;
+            (name, text)
+        } else {
+            let name = format!("template {}", tag);
+            if ui.fragments.contains(&name) {
+                return Err(LoadError::Syntax { position, message: format!("Template {} includes itself", tag) });
+            }
+            // Checked in `is_fragment()`
+            let template = ui.templates.get(&tag).unwrap();
+            let text = template.expand(&tag, &attributes).map_err(|message| LoadError::Syntax { position, message })?;
+            (name, text)
+        };
+        ui.fragments.push(name.clone());
+        let result = UI::parse_xml(ui, &text, stack, strict, depth + 1);
+        ui.fragments.pop();
+        result.map_err(|error| LoadError::Fragment { position, name, error: Box::new(error) })
+    }
+
+    /// Adds the loaded element to the view on top of the stack, or makes it the root if the stack is empty
+    fn add_element(ui: &mut UI, stack: &[Element], element: Element, position: Position) -> Result<(), LoadError> {
+        let parent = match stack.last() {
+            Some(parent) => Rc::clone(parent),
+            None => match &ui.root {
+                None => {
+                    ui.add_view(element);
+                    return Ok(());
                 }
-                Cow::Owned(c) => {
-                    String::from_utf8(c.to_vec()).unwrap()
+                Some(_) => return UI::check_root(ui, stack, position)
+            }
+        };
+        let mut parent_ref = parent.borrow_mut();
+        let tag = ui.get_type_name(&*parent_ref).unwrap_or_default().to_owned();
+        match parent_ref.as_container_mut() {
+            None => Err(LoadError::NotContainer { position, tag }),
+            Some(container) => {
+                element.borrow().set_parent(Some(Rc::downgrade(&parent)));
+                container.add_view(element);
+                Ok(())
+            }
+        }
+    }
+
+    /// Fails if the view at `position` would be the second root of the layout
+    fn check_root(ui: &UI, stack: &[Element], position: Position) -> Result<(), LoadError> {
+        if stack.is_empty() && ui.root.is_some() {
+            return Err(LoadError::Syntax { position, message: String::from("Layout can have only one root view") });
+        }
+        Ok(())
+    }
+
+    fn is_style(e: &BytesStart) -> bool {
+        matches!(e.name().as_ref(), b"Style" | b"Stylesheet")
+    }
+
+    /// Adds the rule of `<Style>` to the stylesheet, or loads the file from `src` of `<Stylesheet>`
+    fn parse_style(ui: &mut UI, e: &BytesStart, position: Position, strict: bool) -> Result<(), LoadError> {
+        let tag = String::from_utf8_lossy(e.name().as_ref()).to_string();
+        let mut attributes = get_unescaped_attributes(e);
+        let mut problems = Vec::new();
+        for (name, value) in attributes.iter_mut() {
+            match ui.resolve(value) {
+                Ok(resolved) => *value = resolved,
+                Err(message) => problems.push((name.clone(), message))
+            }
+        }
+        if tag == "Stylesheet" {
+            if let Some((_, path)) = attributes.iter().find(|(name, _)| name == "src") {
+                let mut warnings = Vec::new();
+                let result = Stylesheet::load(path, &mut warnings);
+                UI::warn_file(ui, path, warnings, position, strict)?;
+                match result {
+                    Ok(stylesheet) => ui.stylesheet.append(stylesheet),
+                    Err(error) => UI::warn_file(ui, path, vec![error], position, strict)?
                 }
+            }
+        } else if let Some(rule) = StyleRule::parse(&attributes, &mut problems) {
+            ui.stylesheet.add_rule(rule);
+        }
+        for (attribute, message) in problems {
+            UI::warn(ui, LoadError::Attribute { position, tag: tag.clone(), attribute, message }, strict)?;
+        }
+        Ok(())
+    }
+
+    /// Keeps the problem in warnings, or fails with it when loading strictly
+    fn warn(ui: &mut UI, error: LoadError, strict: bool) -> Result<(), LoadError> {
+        if strict {
+            return Err(error);
+        }
+        ui.warnings.push(error);
+        Ok(())
+    }
+
+    /// Warns about problems in the file, that the tag at `position` loads
+    fn warn_file(ui: &mut UI, path: &str, errors: Vec<LoadError>, position: Position, strict: bool) -> Result<(), LoadError> {
+        for error in errors {
+            let error = LoadError::Fragment { position, name: format!("file {}", path), error: Box::new(error) };
+            UI::warn(ui, error, strict)?;
+        }
+        Ok(())
+    }
+
+    fn parse_element(ui: &mut UI, e: &BytesStart, position: Position, strict: bool) -> Result<Element, LoadError> {
+        let view_type = String::from_utf8_lossy(e.name().as_ref()).to_string();
+        let view = match ui.try_create(&view_type) {
+            None => return Err(LoadError::UnknownType { position, tag: view_type }),
+            Some(view) => view
+        };
+        let known = ui.get_properties(&view_type).unwrap_or_default().to_vec();
+        for attribute in e.attributes() {
+            let attribute = attribute.map_err(|e| LoadError::Syntax { position, message: e.to_string() })?;
+            let name = String::from_utf8_lossy(attribute.key.as_ref()).to_string();
+            let property = known.iter().find(|p| p.name == name);
+            if !known.is_empty() && property.is_none() {
+                let known = known.iter().map(|p| p.name.to_string()).collect();
+                // A handler that the view never calls is always an error
+                let handler = name.starts_with("on_");
+                let error = LoadError::UnknownAttribute { position, tag: view_type.clone(), attribute: name, known };
+                UI::warn(ui, error, strict || handler)?;
+                continue;
+            }
+            let value = attribute.unescape_value().map_err(|e| LoadError::Syntax { position, message: e.to_string() })?;
+            if let Some(key) = parse_binding(&value) {
+                let input = view.borrow().is_input(&name);
+                ui.bindings.push(Binding { view: Rc::downgrade(&view), attribute: name, key: key.to_owned(), input, last: None });
+                continue;
+            }
+            let value = match ui.resolve(&value) {
+                Ok(resolved) => {
+                    if value.starts_with("@string/") {
+                        let localized = Localized { view: Rc::downgrade(&view), attribute: name.clone(), reference: value.to_string() };
+                        ui.localized.push(localized);
+                    }
+                    resolved
+                }
+                Err(message) => return Err(LoadError::Attribute { position, tag: view_type, attribute: name, message })
             };
-            view.borrow_mut().set_any(&name, &value);
-            //println!("Attribute: {} = {}", &name, &value);
+            if let Some(Err(message)) = property.map(|p| p.validate(&value)) {
+                return Err(LoadError::Attribute { position, tag: view_type, attribute: name, message });
+            }
+            if let Some(event) = EventType::from_attribute(&name) {
+                let handler = match ui.handlers.get(&value) {
+                    None => return Err(LoadError::UnknownHandler { position, tag: view_type, attribute: name, handler: value }),
+                    Some(handler) => Rc::clone(handler)
+                };
+                view.borrow_mut().on_event(event, Box::new(move |ui, view| (handler.borrow_mut())(ui, view)));
+                ui.handled.push((Rc::downgrade(&view), name, value));
+                continue;
+            }
+            if let Err(message) = view.borrow_mut().set_any(&name, &value) {
+                return Err(LoadError::Attribute { position, tag: view_type, attribute: name, message });
+            }
+        }
+        Ok(view)
+    }
+
+    /// Writes the tree of views back to XML, that `from_xml()` can load.
+    /// Views have only attributes that differ from a new view of the same type,
+    /// positions and generated ids are skipped.
+    /// Attributes that reference strings are written as references, with all resources before views.
+    /// Styles are written after resources, with references to resources already resolved.
+    pub fn to_xml(&self) -> String {
+        let mut result = String::new();
+        if self.localized.iter().any(|l| l.view.strong_count() > 0) {
+            result.push_str(&self.resources.to_xml(None));
+            for locale in self.get_locales() {
+                result.push_str(&self.locales[locale].to_xml(Some(locale)));
+            }
+        }
+        if !self.stylesheet.is_empty() {
+            result.push_str(&self.stylesheet.to_xml());
+        }
+        if let Some(root) = &self.root {
+            self.write_element(&mut result, root, 0);
+        }
+        result
+    }
+
+    fn write_element(&self, out: &mut String, element: &Element, depth: usize) {
+        let view = element.borrow();
+        let tag = match self.get_type_name(&*view) {
+            None => {
+                println!("Type of view {} is not registered, skipping it", view.get_id());
+                return;
+            }
+            Some(tag) => tag
+        };
+        let indent = "    ".repeat(depth);
+        out.push_str(&format!("{}<{}", indent, tag));
+        for (name, value) in self.get_changed_attributes(tag, &*view) {
+            if self.bindings.iter().any(|b| b.attribute == name && is_same_view(&b.view, &*view)) {
+                continue;
+            }
+            if let Some(reference) = self.get_localized(&*view, name) {
+                out.push_str(&format!(" {}=\"{}\"", name, escape(reference)));
+                continue;
+            }
+            // Such values would be taken for references to resources
+            let prefix = if value.starts_with('@') { "\\" } else { "" };
+            out.push_str(&format!(" {}=\"{}{}\"", name, prefix, escape(&value)));
+        }
+        for (_, attribute, handler) in self.handled.iter().filter(|(v, _, _)| is_same_view(v, &*view)) {
+            out.push_str(&format!(" {}=\"{}\"", attribute, escape(handler)));
+        }
+        for binding in self.bindings.iter().filter(|b| is_same_view(&b.view, &*view)) {
+            out.push_str(&format!(" {}=\"{{{}}}\"", binding.attribute, escape(&binding.key)));
+        }
+        let children = view.as_container().map(|c| c.get_views()).unwrap_or_default();
+        if children.is_empty() {
+            out.push_str("/>\n");
+            return;
+        }
+        out.push_str(">\n");
+        for child in &children {
+            self.write_element(out, child, depth + 1);
+        }
+        out.push_str(&format!("{}</{}>\n", indent, tag));
+    }
+
+    /// Gets attributes of the view, that differ from the defaults of its type
+    fn get_changed_attributes(&self, tag: &str, view: &dyn View) -> Vec<(&'static str, String)> {
+        let properties = self.get_properties(tag).unwrap_or_default();
+        if properties.is_empty() {
+            return Vec::new();
+        }
+        let default = match self.try_create(tag) {
+            None => return Vec::new(),
+            Some(view) => view
+        };
+        let default = default.borrow();
+        let mut result: Vec<(&'static str, String)> = Vec::new();
+        for property in properties {
+            if matches!(property.name, "left" | "top") {
+                continue;
+            }
+            // Sides of padding or margin are already written, if all of them are equal
+            if let Some((group, _)) = property.name.rsplit_once('_') {
+                if matches!(group, "padding" | "margin") && result.iter().any(|(name, _)| *name == group) {
+                    continue;
+                }
+            }
+            if let Some(value) = view.get_any(property.name) {
+                if default.get_any(property.name).as_ref() != Some(&value) {
+                    result.push((property.name, value));
+                }
+            }
         }
-        view
+        // Ids are easier to find in the beginning
+        result.sort_by_key(|(name, _)| *name != "id");
+        result
     }
 
     pub fn get_width(&self) -> u32 {
@@ -198,7 +1333,8 @@ impl UI {
         match root {
             None => false,
             Some(root) => {
-                root.borrow().on_mouse_move(self, position)
+                let changed = root.borrow().on_mouse_move(self, position);
+                self.on_state_changed(changed)
             }
         }
     }
@@ -208,7 +1344,8 @@ impl UI {
         match root {
             None => false,
             Some(root) => {
-                root.borrow().on_mouse_button_down(self, position, button)
+                let changed = root.borrow().on_mouse_button_down(self, position, button);
+                self.on_state_changed(changed)
             }
         }
     }
@@ -218,7 +1355,8 @@ impl UI {
         match root {
             None => false,
             Some(root) => {
-                root.borrow().on_mouse_button_up(self, position, button)
+                let changed = root.borrow().on_mouse_button_up(self, position, button);
+                self.on_state_changed(changed)
             }
         }
     }
@@ -228,7 +1366,8 @@ impl UI {
         match root {
             None => false,
             Some(root) => {
-                root.borrow().on_key_down(self, virtual_key_code, scancode, modifiers)
+                let changed = root.borrow().on_key_down(self, virtual_key_code, scancode, modifiers);
+                self.on_state_changed(changed)
             }
         }
     }
@@ -238,7 +1377,8 @@ impl UI {
         match root {
             None => false,
             Some(root) => {
-                root.borrow().on_key_up(self, virtual_key_code, scancode, modifiers)
+                let changed = root.borrow().on_key_up(self, virtual_key_code, scancode, modifiers);
+                self.on_state_changed(changed)
             }
         }
     }
@@ -248,8 +1388,215 @@ impl UI {
         match root {
             None => false,
             Some(root) => {
-                root.borrow().on_key_char(self, unicode_codepoint, modifiers)
+                let changed = root.borrow().on_key_char(self, unicode_codepoint, modifiers);
+                self.on_state_changed(changed)
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// Checks if the weak reference points to this view
+fn is_same_view(weak: &WeakElement, view: &dyn View) -> bool {
+    weak.upgrade().map(|v| std::ptr::addr_eq(v.as_ptr(), view)).unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use gui::assets::set_test_assets;
+    use gui::events::EventType;
+    use gui::themes::{Recorder, Theme};
+    use gui::ui::{LoadError, UI};
+    use gui::views::{BUTTON_MIN_HEIGHT, BUTTON_MIN_WIDTH};
+
+    fn get_text(ui: &UI, id: &str) -> Option<String> {
+        ui.get_view(id)?.borrow().get_any("text")
+    }
+
+    #[test]
+    fn loaded_locale_stays_after_set_layout() {
+        set_test_assets(&[("strings/sk.xml", r#"<Resources><String name="ok" value="Dobre"/></Resources>"#)]);
+        let mut ui = UI::new(400, 300, Recorder::typeface());
+        ui.load_locale("sk", "strings/sk.xml").unwrap();
+        ui.set_locale("sk");
+        ui.set_layout(r#"<Frame><Resources><String name="ok" value="OK"/></Resources><Button id="ok" text="@string/ok"/></Frame>"#).unwrap();
+        assert_eq!(get_text(&ui, "ok").as_deref(), Some("Dobre"));
+
+        ui.set_layout(r#"<Frame><Button id="ok" text="@string/ok"/></Frame>"#).unwrap();
+        assert_eq!(get_text(&ui, "ok").as_deref(), Some("Dobre"));
+    }
+
+    #[test]
+    fn reload_replaces_only_what_layout_defined() {
+        set_test_assets(&[
+            ("styles.xml", r##"<Stylesheet><Style select="Button" background="#zz"/></Stylesheet>"##),
+            ("values.xml", r#"<Resources><Dimen name="gap" value="4"/></Resources>"#)
+        ]);
+        let mut ui = UI::new(400, 300, Recorder::typeface());
+        ui.load_stylesheet("styles.xml").unwrap();
+        ui.load_resources("values.xml").unwrap();
+        assert_eq!(ui.get_warnings().len(), 1);
+
+        let first = r##"<Resources><String name="title" value="One"/></Resources>
+            <Style select="Label" color="#zz"/>
+            <Frame><Label id="title" text="@string/title" margin="@dimen/gap"/></Frame>"##;
+        ui.set_layout(first).unwrap();
+        assert_eq!(ui.get_warnings().len(), 2);
+        assert_eq!(ui.get_stylesheet().get_rules().len(), 2);
+        assert_eq!(get_text(&ui, "title").as_deref(), Some("One"));
+
+        ui.set_layout(r#"<Frame><Label id="title" text="Two" margin="@dimen/gap"/></Frame>"#).unwrap();
+        assert_eq!(ui.get_warnings().len(), 1);
+        assert_eq!(ui.get_stylesheet().get_rules().len(), 1);
+        assert_eq!(ui.get_resources().get("string", "title"), None);
+        assert_eq!(ui.get_resources().get("dimen", "gap"), Some("4"));
+        assert_eq!(get_text(&ui, "title").as_deref(), Some("Two"));
+    }
+
+    #[test]
+    fn list_calls_click_handlers() {
+        let clicks = Rc::new(Cell::new(0));
+        let mut ui = UI::new(400, 300, Recorder::typeface());
+        let counter = Rc::clone(&clicks);
+        ui.register_handler("pick", Box::new(move |_, _| { counter.set(counter.get() + 1); true }));
+        ui.set_layout(r#"<Frame><List id="list1" on_click="pick"/><List id="list2"/></Frame>"#).unwrap();
+        let counter = Rc::clone(&clicks);
+        assert!(ui.on_event("list2", EventType::Click, Box::new(move |_, _| { counter.set(counter.get() + 10); true })));
+
+        for id in ["list1", "list2"] {
+            let view = ui.get_view(id).unwrap();
+            assert!(view.borrow().click(&mut ui));
+        }
+        assert_eq!(clicks.get(), 11);
+    }
+
+    #[test]
+    fn handler_that_view_never_calls_fails_the_layout() {
+        let mut ui = UI::new(400, 300, Recorder::typeface());
+        ui.register_handler("press", Box::new(|_, _| true));
+        let result = ui.set_layout(r#"<Frame><Button id="ok" on_mouse_down="press"/></Frame>"#);
+        assert!(matches!(result, Err(LoadError::UnknownAttribute { ref attribute, .. }) if attribute == "on_mouse_down"));
+    }
+
+    #[test]
+    fn wrong_state_flags_are_reported() {
+        set_test_assets(&[("theme.xml", r##"<Theme name="flags"><Style widget="Button"><State pressed="no" background="#000000"/></Style></Theme>"##)]);
+        let mut ui = UI::new(400, 300, Recorder::typeface());
+        ui.load_theme("theme.xml").unwrap();
+        let layout = r##"<Style select="Button" hovered="yes" background="#ffffff"/>
+            <Style select="Label" pressed="true" color="#000000"/>
+            <Frame><Button id="ok"/></Frame>"##;
+        ui.set_layout(layout).unwrap();
+
+        let flags: Vec<_> = ui.get_warnings().iter().filter_map(|w| match w {
+            LoadError::Attribute { attribute, .. } => Some(attribute.as_str()),
+            _ => None
+        }).collect();
+        assert_eq!(flags, vec!["pressed", "hovered"]);
+        // The rule with wrong flag is skipped, it would match any state otherwise
+        assert_eq!(ui.get_stylesheet().get_rules().len(), 1);
+        assert!(UI::from_xml_strict(layout, 400, 300, Recorder::typeface()).is_err());
+    }
+
+    #[test]
+    fn to_xml_loads_back_to_same_tree() {
+        let clicks = Rc::new(Cell::new(0));
+        let new_ui = |clicks: &Rc<Cell<i32>>| {
+            let mut ui = UI::new(400, 300, Recorder::typeface());
+            let counter = Rc::clone(clicks);
+            ui.register_handler("save", Box::new(move |_, _| { counter.set(counter.get() + 1); true }));
+            ui.set_locale("sk");
+            ui
+        };
+        let layout = r##"<Resources><String name="title" value="Title"/></Resources>
+            <Resources locale="sk"><String name="title" value="Nadpis"/></Resources>
+            <Style select="Button" pressed="true" background="#112233"/>
+            <Frame id="root" padding="4">
+                <Label id="title" text="@string/title"/>
+                <Label id="mail" text="\@home &amp; &quot;work&quot;"/>
+                <Button id="ok" text="OK" on_click="save"/>
+                <Edit id="name" text="{name}"/>
+            </Frame>"##;
+        let mut first = new_ui(&clicks);
+        first.set_layout(layout).unwrap();
+        first.set_value("name", "Ann");
+        let xml = first.to_xml();
+
+        let mut second = new_ui(&clicks);
+        second.set_layout(&xml).unwrap();
+        assert_eq!(second.to_xml(), xml);
+        assert!(second.get_warnings().is_empty());
+
+        assert_eq!(get_text(&second, "title").as_deref(), Some("Nadpis"));
+        second.set_locale("");
+        assert_eq!(get_text(&second, "title").as_deref(), Some("Title"));
+        assert_eq!(get_text(&second, "mail").as_deref(), Some("@home & \"work\""));
+        assert_eq!(second.get_stylesheet().get_rules().len(), 1);
+
+        assert_eq!(get_text(&second, "name").as_deref(), Some(""));
+        second.set_value("name", "Ann");
+        assert_eq!(get_text(&second, "name").as_deref(), Some("Ann"));
+
+        let ok = second.get_view("ok").unwrap();
+        assert!(ok.borrow().click(&mut second));
+        assert_eq!(clicks.get(), 1);
+    }
+
+    #[test]
+    fn font_style_is_only_written_when_set() {
+        let mut ui = UI::new(400, 300, Recorder::typeface());
+        ui.set_layout(r#"<Frame font_style="Bold"><Label id="font" font="Roboto"/><Label id="style" font_style="Italic"/></Frame>"#).unwrap();
+        let font = ui.get_view("font").unwrap();
+        assert_eq!(font.borrow().get_any("font").as_deref(), Some("Roboto"));
+        assert_eq!(font.borrow().get_any("font_style"), None);
+        let style = ui.get_view("style").unwrap();
+        assert_eq!(style.borrow().get_any("font"), None);
+        assert!(style.borrow().get_any("font_style").is_some());
+        assert!(!ui.to_xml().contains(r#"font="Roboto" font_style"#));
+    }
+
+    #[test]
+    fn text_is_not_kept_when_font_is_missing() {
+        set_test_assets(&[]);
+        let mut ui = UI::new(400, 300, Recorder::typeface());
+        ui.set_layout(r#"<Frame><Label id="label" text="Quite a long text for the label"/></Frame>"#).unwrap();
+        ui.layout(400, 300, 1.0);
+        let label = ui.get_view("label").unwrap();
+        assert!(label.borrow().get_content_size().0 > BUTTON_MIN_WIDTH);
+
+        label.borrow_mut().set_any("font", "Missing").unwrap();
+        ui.layout(400, 300, 1.0);
+        assert_eq!(label.borrow().get_content_size(), (BUTTON_MIN_WIDTH, BUTTON_MIN_HEIGHT));
+    }
+
+    #[test]
+    fn unknown_types_are_not_created() {
+        let ui = UI::new(400, 300, Recorder::typeface());
+        assert!(ui.try_create("Button").is_some());
+        assert!(ui.try_create("Slider").is_none());
+    }
+
+    #[test]
+    fn only_styles_are_inside_of_styles() {
+        let mut ui = UI::new(400, 300, Recorder::typeface());
+        let xml = "<Stylesheet>\n    <Style select=\"Button\">\n        <Button id=\"inner\"/>\n    </Style>\n</Stylesheet>\n<Frame/>";
+        let error = ui.set_layout(xml).unwrap_err();
+        assert!(matches!(error, LoadError::Syntax { .. }));
+        let position = error.position().unwrap();
+        assert_eq!((position.line, position.column), (3, 9));
+        assert!(ui.get_view("inner").is_none());
+    }
+
+    #[test]
+    fn template_parameters_are_escaped() {
+        let mut ui = UI::new(400, 300, Recorder::typeface());
+        let xml = r#"<Template name="Caption" text="none"><Label id="caption" text="${text}"/></Template>
+            <Frame><Caption text="Tom &amp; &quot;Jerry&quot; &lt;3"/></Frame>"#;
+        ui.set_layout(xml).unwrap();
+        assert_eq!(get_text(&ui, "caption").as_deref(), Some("Tom & \"Jerry\" <3"));
+
+        ui.set_layout(r#"<Template name="Caption"><Label id="caption" text="${text}"/></Template><Frame><Caption text='1 < 2 & "3"'/></Frame>"#).unwrap();
+        assert_eq!(get_text(&ui, "caption").as_deref(), Some("1 < 2 & \"3\""));
+    }
+}
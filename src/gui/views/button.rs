@@ -1,20 +1,20 @@
-use std::cell::RefCell;
+use std::cell::{RefCell, RefMut};
 use std::cmp::max;
 use std::collections::HashMap;
 
 use speedy2d::dimen::Vector2;
-use speedy2d::font::{TextAlignment, TextLayout, TextOptions};
+use speedy2d::font::{TextAlignment, TextOptions};
 use speedy2d::window::MouseButton;
 
-use crate::gui::assets::get_font;
+use crate::gui::assets::layout_text;
 use crate::gui::events::EventType;
 use crate::gui::common::DEFAULT_TEXT_SIZE;
-use crate::gui::themes::{FontStyle, Theme, Typeface, ViewState};
+use crate::gui::themes::{Theme, Typeface, ViewState};
+use crate::gui::properties::{Property, with_defaults};
 use crate::gui::traits::{Element, View, WeakElement};
 use crate::gui::types::{Point, Rect, rect};
 use crate::gui::ui::UI;
-use crate::gui::views::{Borders, Dimension};
-use crate::gui::styles::selector::FontSelector;
+use crate::gui::views::{Borders, Dimension, click_property, text_properties};
 use crate::gui::views::{FieldsMain, FieldsTexted};
 use super::{BUTTON_MIN_HEIGHT, BUTTON_MIN_WIDTH};
 
@@ -35,7 +35,6 @@ impl Button {
                 line_height: 0f32,
                 single_line: true,
                 cached_text: None,
-                font: FontSelector::new(),
                 listeners: HashMap::new()
             })
         }
@@ -53,87 +52,54 @@ impl Button {
         self.layout_text(self.get_rect_width(), single_line, scale);
     }
 
-    fn get_typeface(&self, parent_typeface: &Typeface) -> Typeface {
-        match &self.state.borrow().main.typeface {
-            None => parent_typeface.clone(),
-            Some(t) => {
-                if t.font_name.is_empty() {
-                    let mut parent = parent_typeface.clone();
-                    parent.font_style = t.font_style.clone();
-                    parent
-                } else {
-                    t.clone()
-                }
-            }
-        }
-    }
-
-    fn set_font(&self, font_name: &str) {
-        let typeface = match self.state.borrow_mut().main.typeface.take() {
-            None => Typeface { font_name: font_name.to_owned(), font_style: FontStyle::Regular },
-            Some(mut t) => {
-                t.font_name = font_name.to_owned();
-                t
-            }
-        };
-        self.state.borrow_mut().main.typeface = Some(typeface);
-    }
-
-    fn set_font_style(&self, style: &str) {
-        let font_style = FontStyle::from(style);
-        let typeface = match self.state.borrow_mut().main.typeface.take() {
-            None => Typeface { font_name: String::new(), font_style },
-            Some(t) => Typeface { font_name: t.font_name, font_style },
-        };
-        self.state.borrow_mut().main.typeface = Some(typeface)
-    }
-
     fn layout_text(&self, max_width: i32, single_line: bool, scale: f64) {
         if max_width <= 0 {
             self.state.borrow_mut().cached_text = None;
             return;
         }
-        let typeface = self.state.borrow().main.typeface.clone();
+        let typeface = self.state.borrow().main.resolved_typeface.clone();
         if let Some(typeface) = typeface {
-            if let Some(font) = get_font(&typeface.font_name, &typeface.font_style.to_string()) {
-                let options = match single_line {
-                    true => TextOptions::new(),
-                    false => TextOptions::new().with_wrap_to_width(max_width as f32, TextAlignment::Left)
-                };
-                let size = self.state.borrow().text_size * scale as f32;
-                let text = font.layout_text(&self.state.borrow().text, size, options);
-                self.state.borrow_mut().cached_text = Some(text);
-            }
+            let options = match single_line {
+                true => TextOptions::new(),
+                false => TextOptions::new().with_wrap_to_width(max_width as f32, TextAlignment::Left)
+            };
+            let size = self.state.borrow().text_size * scale as f32;
+            let text = layout_text(&typeface, &self.state.borrow().text, size, options);
+            self.state.borrow_mut().cached_text = text;
         }
     }
 }
 
 impl View for Button {
-    fn set_any(&mut self, name: &str, value: &str) {
+    fn set_any(&mut self, name: &str, value: &str) -> Result<(), String> {
         match name {
-            "left" => { self.set_x(value.parse().unwrap()) }
-            "top" => { self.set_y(value.parse().unwrap()) }
-            "width" => { self.set_width(value.parse().unwrap()) }
-            "height" => { self.set_height(value.parse().unwrap()) }
-            "padding" => { self.state.borrow_mut().main.padding.set_all(value.parse().unwrap_or(0)) }
-            "padding_top" => { self.state.borrow_mut().main.padding.top = value.parse().unwrap_or(0) }
-            "padding_left" => { self.state.borrow_mut().main.padding.left = value.parse().unwrap_or(0) }
-            "padding_right" => { self.state.borrow_mut().main.padding.right = value.parse().unwrap_or(0) }
-            "padding_bottom" => { self.state.borrow_mut().main.padding.bottom = value.parse().unwrap_or(0) }
-            "margin" => { self.state.borrow_mut().main.margin.set_all(value.parse().unwrap_or(0)) }
-            "margin_left" => { self.state.borrow_mut().main.margin.left = value.parse().unwrap_or(0) }
-            "margin_right" => { self.state.borrow_mut().main.margin.right = value.parse().unwrap_or(0) }
-            "margin_top" => { self.state.borrow_mut().main.margin.top = value.parse().unwrap_or(0) }
-            "margin_bottom" => { self.state.borrow_mut().main.margin.bottom = value.parse().unwrap_or(0) }
-            "id" => { self.set_id(value) }
             "text" => { self.set_text(value) }
-            "font" => { self.set_font(value) }
-            "font_style" => { self.set_font_style(value) }
-            "break" => { self.state.borrow_mut().main.break_line = value.parse().unwrap_or(false) }
-            &_ => {}
+            _ => self.state.borrow_mut().main.set_any(name, value)?
+        }
+        Ok(())
+    }
+
+    fn get_any(&self, name: &str) -> Option<String> {
+        let state = self.state.borrow();
+        match name {
+            "text" => Some(state.text.clone()),
+            _ => state.main.get_any(name)
         }
     }
 
+    fn properties() -> Vec<Property> {
+        let defaults = [
+            ("padding", "4"),
+            ("padding_top", "4"),
+            ("padding_left", "4"),
+            ("padding_right", "4"),
+            ("padding_bottom", "4")
+        ];
+        let mut properties = with_defaults(text_properties(), &defaults);
+        properties.push(click_property());
+        properties
+    }
+
     fn set_parent(&self, parent: Option<WeakElement>) {
         self.state.borrow_mut().main.parent = parent;
     }
@@ -150,21 +116,16 @@ impl View for Button {
         }
     }
 
-    fn layout_content(&mut self, x: i32, y: i32, width: i32, height: i32, typeface: &Typeface, scale: f64) -> Rect<i32> {
-        //println!("{} for width {}", self.get_id(), width);
-        let typeface = self.get_typeface(typeface);
-        self.state.borrow_mut().main.typeface = Some(typeface);
+    fn measure(&mut self, width: i32, height: i32, typeface: &Typeface, scale: f64) -> (i32, i32) {
+        let typeface = self.state.borrow().main.get_typeface(typeface);
+        self.state.borrow_mut().main.resolved_typeface = Some(typeface);
         self.state.borrow_mut().main.scale = scale;
         let padding = self.get_padding(scale);
-        let horizontal = padding.left + padding.right;
-        let vertical = padding.top + padding.bottom;
-        let (new_width, _new_height) = self.calculate_size(width.max(BUTTON_MIN_WIDTH) - horizontal, height.max(BUTTON_MIN_HEIGHT) - vertical, scale);
+        let (max_width, _) = self.calculate_size(width, height, scale);
         let single_line = self.state.borrow().single_line;
-        self.layout_text(new_width, single_line, scale);
-        let (width, height) = self.calculate_full_size(scale);
-        let rect = rect((x, y), (x + width, y + height));
-        self.set_rect(rect);
-        rect
+        self.layout_text(max_width.max(BUTTON_MIN_WIDTH) - padding.left - padding.right, single_line, scale);
+        let content = self.calculate_full_size(scale);
+        self.resolve_size(width, height, content, scale)
     }
 
     fn fits_in_rect(&self, width: i32, height: i32, _scale: f64) -> bool {
@@ -181,7 +142,9 @@ impl View for Button {
         rect.move_by(origin);
         theme.push_clip();
         theme.clip_rect(rect);
-        theme.draw_button_back(rect, state.main.state);
+        if !theme.draw_styled_back(rect, state.main.state, &state.main.background) {
+            theme.draw_button_back(rect, state.main.state);
+        }
         theme.draw_button_body(rect, state.main.state);
         // TODO use padding
         if let Some(text) = &state.cached_text {
@@ -271,13 +234,17 @@ impl View for Button {
     }
 
     fn set_id(&mut self, id: &str) {
-        self.state.borrow_mut().main.id = id.to_owned();
+        self.state.borrow_mut().main.set_id(id);
     }
 
     fn get_id(&self) -> String {
         self.state.borrow().main.id.clone()
     }
 
+    fn get_main(&self) -> Option<RefMut<'_, FieldsMain>> {
+        Some(RefMut::map(self.state.borrow_mut(), |s| &mut s.main))
+    }
+
     fn on_event(&mut self, event: EventType, func: Box<dyn FnMut(&mut UI, &dyn View) -> bool>) {
         self.state.borrow_mut().listeners.insert(event, func);
     }
@@ -1,18 +1,18 @@
-use std::cell::RefCell;
+use std::cell::{RefCell, RefMut};
 use std::cmp::max;
 use std::collections::HashMap;
 use std::time::Instant;
 use speedy2d::dimen::Vector2;
-use speedy2d::font::{TextLayout, TextOptions};
+use speedy2d::font::TextOptions;
 use speedy2d::window::{KeyScancode, ModifiersState, MouseButton, VirtualKeyCode};
 
-use crate::gui::assets::get_font;
+use crate::gui::assets::layout_text;
 use crate::gui::events::EventType;
 use crate::gui;
 use crate::gui::common::{delete_char, insert_char};
-use crate::gui::views::Borders;
-use crate::gui::styles::selector::FontSelector;
-use crate::gui::themes::{FontStyle, Theme, Typeface, ViewState};
+use crate::gui::views::{Borders, click_property, text_properties};
+use crate::gui::themes::{Theme, Typeface, ViewState};
+use crate::gui::properties::{Property, with_defaults};
 use crate::gui::traits::{Element, View, WeakElement};
 use crate::gui::types::{Point, Rect, rect};
 use crate::gui::ui::UI;
@@ -37,7 +37,6 @@ impl Edit {
             line_height: 0f32,
             single_line: true,
             cached_text: None,
-            font: FontSelector::new(),
             listeners: HashMap::new()
         };
         fields.main.padding = Borders::with_padding(4);
@@ -67,54 +66,17 @@ impl Edit {
         self.layout_text(self.get_rect_width(), scale);
     }
 
-    fn get_typeface(&self, parent_typeface: &Typeface) -> Typeface {
-        match &self.state.borrow().main.typeface {
-            None => parent_typeface.clone(),
-            Some(t) => {
-                if t.font_name.is_empty() {
-                    let mut parent = parent_typeface.clone();
-                    parent.font_style = t.font_style.clone();
-                    parent
-                } else {
-                    t.clone()
-                }
-            }
-        }
-    }
-
-    fn set_font(&self, font_name: &str) {
-        let typeface = match self.state.borrow_mut().main.typeface.take() {
-            None => Typeface { font_name: font_name.to_owned(), font_style: FontStyle::Regular },
-            Some(mut t) => {
-                t.font_name = font_name.to_owned();
-                t
-            }
-        };
-        self.state.borrow_mut().main.typeface = Some(typeface);
-    }
-
-    fn set_font_style(&self, style: &str) {
-        let font_style = FontStyle::from(style);
-        let typeface = match self.state.borrow_mut().main.typeface.take() {
-            None => Typeface { font_name: String::new(), font_style },
-            Some(t) => Typeface { font_name: t.font_name, font_style },
-        };
-        self.state.borrow_mut().main.typeface = Some(typeface)
-    }
-
     #[allow(unused_variables)]
     fn layout_text(&self, width: i32, scale: f64) {
         if self.state.borrow().text.len() == 0 {
             self.state.borrow_mut().cached_text = None;
             return;
         }
-        let typeface = self.state.borrow().main.typeface.clone();
+        let typeface = self.state.borrow().main.resolved_typeface.clone();
         if let Some(typeface) = typeface {
-            if let Some(font) = get_font(&typeface.font_name, &typeface.font_style.to_string()) {
-                let options = TextOptions::new();
-                let text = font.layout_text(&self.state.borrow().text, self.state.borrow().text_size, options);
-                self.state.borrow_mut().cached_text = Some(text);
-            }
+            let options = TextOptions::new();
+            let text = layout_text(&typeface, &self.state.borrow().text, self.state.borrow().text_size, options);
+            self.state.borrow_mut().cached_text = text;
         }
     }
 
@@ -186,11 +148,11 @@ impl Edit {
             return self.state.borrow().line_height;
         }
 
-        let typeface = self.state.borrow().main.typeface.clone();
+        let typeface = self.state.borrow().main.resolved_typeface.clone();
         if let Some(typeface) = typeface {
-            if let Some(font) = get_font(&typeface.font_name, &typeface.font_style.to_string()) {
-                let options = TextOptions::new();
-                let text = font.layout_text("W", self.state.borrow().text_size, options);
+            let options = TextOptions::new();
+            let text = layout_text(&typeface, "W", self.state.borrow().text_size, options);
+            if let Some(text) = text {
                 self.state.borrow_mut().line_height = text.height();
             }
         }
@@ -199,29 +161,38 @@ impl Edit {
 }
 
 impl View for Edit {
-    fn set_any(&mut self, name: &str, value: &str) {
+    fn set_any(&mut self, name: &str, value: &str) -> Result<(), String> {
         match name {
-            "left" => { self.set_x(value.parse().unwrap()) }
-            "top" => { self.set_y(value.parse().unwrap()) }
-            "width" => { self.set_width(value.parse().unwrap()) }
-            "height" => { self.set_height(value.parse().unwrap()) }
-            "padding" => { self.state.borrow_mut().main.padding.set_all(value.parse().unwrap_or(0)) }
-            "padding_top" => { self.state.borrow_mut().main.padding.top = value.parse().unwrap_or(0) }
-            "padding_left" => { self.state.borrow_mut().main.padding.left = value.parse().unwrap_or(0) }
-            "padding_right" => { self.state.borrow_mut().main.padding.right = value.parse().unwrap_or(0) }
-            "padding_bottom" => { self.state.borrow_mut().main.padding.bottom = value.parse().unwrap_or(0) }
-            "margin" => { self.state.borrow_mut().main.margin.set_all(value.parse().unwrap_or(0)) }
-            "margin_left" => { self.state.borrow_mut().main.margin.left = value.parse().unwrap_or(0) }
-            "margin_right" => { self.state.borrow_mut().main.margin.right = value.parse().unwrap_or(0) }
-            "margin_top" => { self.state.borrow_mut().main.margin.top = value.parse().unwrap_or(0) }
-            "margin_bottom" => { self.state.borrow_mut().main.margin.bottom = value.parse().unwrap_or(0) }
-            "id" => { self.set_id(value) }
             "text" => { self.set_text(value) }
-            "font" => { self.set_font(value) }
-            "font_style" => { self.set_font_style(value) }
-            "break" => { self.state.borrow_mut().main.break_line = value.parse().unwrap_or(false) }
-            &_ => {}
+            _ => self.state.borrow_mut().main.set_any(name, value)?
         }
+        Ok(())
+    }
+
+    fn get_any(&self, name: &str) -> Option<String> {
+        let state = self.state.borrow();
+        match name {
+            "text" => Some(state.text.clone()),
+            _ => state.main.get_any(name)
+        }
+    }
+
+    fn is_input(&self, name: &str) -> bool {
+        name == "text"
+    }
+
+    fn properties() -> Vec<Property> {
+        let defaults = [
+            ("width", "max"),
+            ("padding", "4"),
+            ("padding_top", "4"),
+            ("padding_left", "4"),
+            ("padding_right", "4"),
+            ("padding_bottom", "4")
+        ];
+        let mut properties = with_defaults(text_properties(), &defaults);
+        properties.push(click_property());
+        properties
     }
 
     fn set_parent(&self, parent: Option<WeakElement>) {
@@ -240,36 +211,15 @@ impl View for Edit {
         }
     }
 
-    fn layout_content(&mut self, x: i32, y: i32, width: i32, height: i32, typeface: &Typeface, scale: f64) -> Rect<i32> {
-        if self.state.borrow().cached_text.is_none() {
-            let typeface = self.get_typeface(typeface);
-            self.state.borrow_mut().main.typeface = Some(typeface);
+    fn measure(&mut self, width: i32, height: i32, typeface: &Typeface, scale: f64) -> (i32, i32) {
+        let typeface = self.state.borrow().main.get_typeface(typeface);
+        if self.state.borrow().cached_text.is_none() || self.state.borrow().main.resolved_typeface.as_ref() != Some(&typeface) {
+            self.state.borrow_mut().main.resolved_typeface = Some(typeface);
             self.state.borrow_mut().main.scale = scale;
             self.layout_text(width, scale);
         }
-        let (new_width, new_height) = self.calculate_size(width, height, scale);
-        let (w, h) = self.calculate_full_size(scale);
-        let (width, height) = {
-            let state = self.state.borrow_mut();
-            let ww;
-            let hh;
-            match &state.main.width {
-                Dimension::Min => ww = w,
-                Dimension::Max => ww = new_width,
-                Dimension::Dip(dip) => ww = *dip as i32,
-                Dimension::Percent(p) => ww = (width as f32 * p / 100f32).round() as i32
-            }
-            match &state.main.height {
-                Dimension::Min => hh = h,
-                Dimension::Max => hh = new_height,
-                Dimension::Dip(dip) => hh = *dip as i32,
-                Dimension::Percent(p) => hh = (height as f32 * p / 100f32).round() as i32
-            }
-            (ww, hh)
-        };
-        let rect = rect((x, y), (x + width, y + height));
-        self.set_rect(rect);
-        rect
+        let content = self.calculate_full_size(scale);
+        self.resolve_size(width, height, content, scale)
     }
 
     fn fits_in_rect(&self, width: i32, height: i32, _scale: f64) -> bool {
@@ -290,7 +240,9 @@ impl View for Edit {
         // Drawing the back and frame
         theme.push_clip();
         theme.clip_rect(rect);
-        theme.draw_edit_back(rect, state.main.state);
+        if !theme.draw_styled_back(rect, state.main.state, &state.main.background) {
+            theme.draw_edit_back(rect, state.main.state);
+        }
         theme.draw_edit_body(rect, state.main.state);
         theme.pop_clip();
         // Drawing the text
@@ -393,19 +345,24 @@ impl View for Edit {
     }
 
     fn set_id(&mut self, id: &str) {
-        self.state.borrow_mut().main.id = id.to_owned();
+        self.state.borrow_mut().main.set_id(id);
     }
 
     fn get_id(&self) -> String {
         self.state.borrow().main.id.clone()
     }
 
+    fn get_main(&self) -> Option<RefMut<'_, FieldsMain>> {
+        Some(RefMut::map(self.state.borrow_mut(), |s| &mut s.main))
+    }
+
     fn on_event(&mut self, event: EventType, func: Box<dyn FnMut(&mut UI, &dyn View) -> bool>) {
         self.state.borrow_mut().listeners.insert(event, func);
     }
 
     fn click(&self, ui: &mut UI) -> bool {
-        if let Some(mut click) = self.state.borrow_mut().listeners.remove(&EventType::Click) {
+        let listener = self.state.borrow_mut().listeners.remove(&EventType::Click);
+        if let Some(mut click) = listener {
             let result = click(ui, self as &dyn View);
             self.state.borrow_mut().listeners.insert(EventType::Click, click);
             return result;
@@ -438,6 +395,18 @@ impl View for Edit {
         false
     }
 
+    fn on_mouse_button_up(&self, ui: &mut UI, position: Vector2<i32>, button: MouseButton) -> bool {
+        let hit = self.state.borrow().main.rect.hit((position.x, position.y));
+        if matches!(button, MouseButton::Left) && self.state.borrow().main.state.pressed {
+            self.state.borrow_mut().main.state.pressed = false;
+            if hit {
+                self.click(ui);
+            }
+            return true;
+        }
+        false
+    }
+
     fn on_key_down(&self, _ui: &mut UI, virtual_key_code: Option<VirtualKeyCode>, _scancode: KeyScancode, _state: ModifiersState) -> bool {
         if let Some(code) = virtual_key_code {
             match code {
@@ -1,21 +1,23 @@
-use std::cell::RefCell;
+use std::cell::{RefCell, RefMut};
 use std::cmp::max;
 use std::collections::HashMap;
 
-use speedy2d::font::{TextAlignment, TextLayout, TextOptions};
-use crate::gui::assets::get_font;
+use speedy2d::font::{TextAlignment, TextOptions};
+use crate::gui::assets::layout_text;
 use crate::gui::events::EventType;
 
-use crate::gui::themes::{FontStyle, Theme, Typeface, ViewState};
+use crate::gui::themes::{Theme, Typeface, ViewState};
+use crate::gui::properties::Property;
 use crate::gui::traits::{Element, View, WeakElement};
 use crate::gui::types::{Point, Rect, rect};
 use crate::gui::ui::UI;
-use crate::gui::views::{Borders, Dimension};
-use crate::gui::styles::selector::FontSelector;
+use crate::gui::views::{Borders, Dimension, text_properties};
 use crate::gui::views::{BUTTON_MIN_HEIGHT, BUTTON_MIN_WIDTH, FieldsMain, FieldsTexted};
 
 pub struct Label {
-    state: RefCell<FieldsTexted>
+    state: RefCell<FieldsTexted>,
+    /// Width that the text was wrapped to
+    wrap_width: i32
 }
 
 #[allow(dead_code)]
@@ -31,9 +33,9 @@ impl Label {
                 line_height: 0f32,
                 single_line: true,
                 cached_text: None,
-                font: FontSelector::new(),
                 listeners: HashMap::new()
-            })
+            }),
+            wrap_width: 0
         }
     }
 
@@ -44,66 +46,27 @@ impl Label {
         let _ = state.cached_text.take();
     }
 
-    fn get_typeface(&self, parent_typeface: &Typeface) -> Typeface {
-        match &self.state.borrow().main.typeface {
-            None => parent_typeface.clone(),
-            Some(t) => {
-                if t.font_name.is_empty() {
-                    let mut parent = parent_typeface.clone();
-                    parent.font_style = t.font_style.clone();
-                    parent
-                } else {
-                    t.clone()
-                }
-            }
-        }
-    }
-
-    fn set_font(&self, font_name: &str) {
-        let typeface = match self.state.borrow_mut().main.typeface.take() {
-            None => Typeface { font_name: font_name.to_owned(), font_style: FontStyle::Regular },
-            Some(mut t) => {
-                t.font_name = font_name.to_owned();
-                t
-            }
-        };
-        self.state.borrow_mut().main.typeface = Some(typeface);
-    }
-
-    fn set_font_style(&self, style: &str) {
-        let font_style = FontStyle::from(style);
-        let typeface = match self.state.borrow_mut().main.typeface.take() {
-            None => Typeface { font_name: String::new(), font_style },
-            Some(t) => Typeface { font_name: t.font_name, font_style },
-        };
-        self.state.borrow_mut().main.typeface = Some(typeface)
-    }
 }
 
 impl View for Label {
-    fn set_any(&mut self, name: &str, value: &str) {
+    fn set_any(&mut self, name: &str, value: &str) -> Result<(), String> {
         match name {
-            "left" => { self.set_x(value.parse().unwrap()) }
-            "top" => { self.set_y(value.parse().unwrap()) }
-            "width" => { self.set_width(value.parse().unwrap()) }
-            "height" => { self.set_height(value.parse().unwrap()) }
-            "padding" => { self.state.borrow_mut().main.padding.set_all(value.parse().unwrap_or(0)) }
-            "padding_top" => { self.state.borrow_mut().main.padding.top = value.parse().unwrap_or(0) }
-            "padding_left" => { self.state.borrow_mut().main.padding.left = value.parse().unwrap_or(0) }
-            "padding_right" => { self.state.borrow_mut().main.padding.right = value.parse().unwrap_or(0) }
-            "padding_bottom" => { self.state.borrow_mut().main.padding.bottom = value.parse().unwrap_or(0) }
-            "margin" => { self.state.borrow_mut().main.margin.set_all(value.parse().unwrap_or(0)) }
-            "margin_left" => { self.state.borrow_mut().main.margin.left = value.parse().unwrap_or(0) }
-            "margin_right" => { self.state.borrow_mut().main.margin.right = value.parse().unwrap_or(0) }
-            "margin_top" => { self.state.borrow_mut().main.margin.top = value.parse().unwrap_or(0) }
-            "margin_bottom" => { self.state.borrow_mut().main.margin.bottom = value.parse().unwrap_or(0) }
-            "id" => { self.set_id(value) }
             "text" => { self.set_text(value) }
-            "font" => { self.set_font(value) }
-            "font_style" => { self.set_font_style(value) }
-            "break" => { self.state.borrow_mut().main.break_line = value.parse().unwrap_or(false) }
-            &_ => {}
+            _ => self.state.borrow_mut().main.set_any(name, value)?
         }
+        Ok(())
+    }
+
+    fn get_any(&self, name: &str) -> Option<String> {
+        let state = self.state.borrow();
+        match name {
+            "text" => Some(state.text.clone()),
+            _ => state.main.get_any(name)
+        }
+    }
+
+    fn properties() -> Vec<Property> {
+        text_properties()
     }
 
     fn set_parent(&self, parent: Option<WeakElement>) {
@@ -122,24 +85,25 @@ impl View for Label {
         }
     }
 
-    fn layout_content(&mut self, x: i32, y: i32, width: i32, _height: i32, typeface: &Typeface, scale: f64) -> Rect<i32> {
-        if self.state.borrow().cached_text.is_some() {
-            // TODO check if area changed
-            return self.get_rect();
-        }
-
-        self.state.borrow_mut().main.scale = scale;
-        let typeface = self.get_typeface(typeface);
-        if let Some(font) = get_font(&typeface.font_name, &typeface.font_style.to_string()) {
+    fn measure(&mut self, width: i32, height: i32, typeface: &Typeface, scale: f64) -> (i32, i32) {
+        let typeface = self.state.borrow().main.get_typeface(typeface);
+        let padding = self.get_padding(scale);
+        let (max_width, _) = self.calculate_size(width, height, scale);
+        let wrap_width = max_width - padding.left - padding.right;
+        let laid_out = self.state.borrow().cached_text.is_some()
+            && self.state.borrow().main.resolved_typeface.as_ref() == Some(&typeface)
+            && self.wrap_width == wrap_width;
+        if !laid_out {
+            self.state.borrow_mut().main.scale = scale;
+            self.state.borrow_mut().main.resolved_typeface = Some(typeface.clone());
             let options = TextOptions::new()
-                .with_wrap_to_width(width as f32, TextAlignment::Left);
-            let text = font.layout_text(&self.state.borrow().text, self.state.borrow().text_size, options);
-            self.state.borrow_mut().cached_text = Some(text);
+                .with_wrap_to_width(wrap_width as f32, TextAlignment::Left);
+            let text = layout_text(&typeface, &self.state.borrow().text, self.state.borrow().text_size, options);
+            self.state.borrow_mut().cached_text = text;
+            self.wrap_width = wrap_width;
         }
-        let (width, height) = self.calculate_full_size(scale);
-        let rect = rect((x, y), (x + width, y + height));
-        self.set_rect(rect.clone());
-        rect
+        let content = self.calculate_full_size(scale);
+        self.resolve_size(width, height, content, scale)
     }
 
     fn fits_in_rect(&self, width: i32, height: i32, _scale: f64) -> bool {
@@ -156,6 +120,7 @@ impl View for Label {
         rect.move_by(origin);
         theme.push_clip();
         theme.clip_rect(rect);
+        theme.draw_styled_back(rect, state.main.state, &state.main.background);
         if let Some(text) = &self.state.borrow().cached_text {
             let x = (self.get_rect_width() as f32 - text.width()) / 2f32;
             let y = (self.get_rect_height() as f32 - text.height()) / 2f32;
@@ -235,19 +200,29 @@ impl View for Label {
     }
 
     fn set_id(&mut self, id: &str) {
-        self.state.borrow_mut().main.id = id.to_owned();
+        self.state.borrow_mut().main.set_id(id);
     }
 
     fn get_id(&self) -> String {
         self.state.borrow().main.id.clone()
     }
 
+    fn get_main(&self) -> Option<RefMut<'_, FieldsMain>> {
+        Some(RefMut::map(self.state.borrow_mut(), |s| &mut s.main))
+    }
+
     fn on_event(&mut self, event: EventType, func: Box<dyn FnMut(&mut UI, &dyn View) -> bool>) {
         self.state.borrow_mut().listeners.insert(event, func);
     }
 
-    fn click(&self, _ui: &mut UI) -> bool {
-        todo!()
+    fn click(&self, ui: &mut UI) -> bool {
+        let listener = self.state.borrow_mut().listeners.remove(&EventType::Click);
+        if let Some(mut click) = listener {
+            let result = click(ui, self as &dyn View);
+            self.state.borrow_mut().listeners.insert(EventType::Click, click);
+            return result;
+        }
+        false
     }
 }
 
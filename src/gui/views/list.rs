@@ -1,16 +1,18 @@
-use std::cell::RefCell;
+use std::cell::{RefCell, RefMut};
+use std::collections::HashMap;
 use std::rc::Rc;
 use speedy2d::dimen::Vector2;
-use speedy2d::font::{FormattedTextBlock, TextLayout, TextOptions};
+use speedy2d::font::{FormattedTextBlock, TextOptions};
 use speedy2d::window::{KeyScancode, ModifiersState, MouseButton, VirtualKeyCode};
-use gui::assets::get_font;
+use gui::assets::layout_text;
 use gui::common::DEFAULT_TEXT_SIZE;
 use gui::events::EventType;
 use gui::themes::{Theme, Typeface, ViewState};
+use gui::properties::Property;
 use gui::traits::{Element, View, WeakElement};
 use gui::types::{Point, Rect, rect};
-use gui::ui::UI;
-use gui::views::{Borders, Dimension, FieldsMain};
+use gui::ui::{EventListener, UI};
+use gui::views::{Borders, Dimension, FieldsMain, click_property, main_properties};
 
 pub struct List {
     state: RefCell<FieldsMain>,
@@ -18,7 +20,8 @@ pub struct List {
     texts: RefCell<Vec<Option<Rc<FormattedTextBlock>>>>,
     text_size: f32,
     scroll_y: RefCell<i32>,
-    selected: RefCell<Option<usize>>
+    selected: RefCell<Option<usize>>,
+    listeners: RefCell<HashMap<EventType, EventListener>>
 }
 
 impl List {
@@ -29,19 +32,19 @@ impl List {
             texts: RefCell::new(vec![]),
             text_size: DEFAULT_TEXT_SIZE,
             scroll_y: RefCell::new(0),
-            selected: RefCell::new(None)
+            selected: RefCell::new(None),
+            listeners: RefCell::new(HashMap::new())
         }
     }
 
     pub fn set_items(&mut self, items: Vec<String>) {
         self.items = RefCell::new(items);
         self.texts.borrow_mut().clear();
-        let typeface = self.state.borrow().typeface.clone().unwrap();
+        let typeface = self.state.borrow().resolved_typeface.clone().unwrap();
         let scale = self.state.borrow().scale as f32;
         for i in self.items.borrow().iter() {
-            if let Some(font) = get_font(&typeface.font_name, &typeface.font_style.to_string()) {
-                let options = TextOptions::new();
-                let text = font.layout_text(&i, self.text_size * scale, options);
+            let options = TextOptions::new();
+            if let Some(text) = layout_text(&typeface, i, self.text_size * scale, options) {
                 self.texts.borrow_mut().push(Some(text));
             }
         }
@@ -98,26 +101,14 @@ impl List {
 }
 
 impl View for List {
-    fn set_any(&mut self, name: &str, value: &str) {
-        match name {
-            "left" => { self.set_x(value.parse().unwrap()) }
-            "top" => { self.set_y(value.parse().unwrap()) }
-            "width" => { self.set_width(value.parse().unwrap()) }
-            "height" => { self.set_height(value.parse().unwrap()) }
-            "padding" => { self.state.borrow_mut().padding.set_all(value.parse().unwrap_or(0)) }
-            "padding_top" => { self.state.borrow_mut().padding.top = value.parse().unwrap_or(0) }
-            "padding_left" => { self.state.borrow_mut().padding.left = value.parse().unwrap_or(0) }
-            "padding_right" => { self.state.borrow_mut().padding.right = value.parse().unwrap_or(0) }
-            "padding_bottom" => { self.state.borrow_mut().padding.bottom = value.parse().unwrap_or(0) }
-            "margin" => { self.state.borrow_mut().margin.set_all(value.parse().unwrap_or(0)) }
-            "margin_left" => { self.state.borrow_mut().margin.left = value.parse().unwrap_or(0) }
-            "margin_right" => { self.state.borrow_mut().margin.right = value.parse().unwrap_or(0) }
-            "margin_top" => { self.state.borrow_mut().margin.top = value.parse().unwrap_or(0) }
-            "margin_bottom" => { self.state.borrow_mut().margin.bottom = value.parse().unwrap_or(0) }
-            "id" => { self.set_id(value) }
-            "break" => { self.state.borrow_mut().break_line = value.parse().unwrap_or(false) }
-            &_ => {}
-        }
+    fn set_any(&mut self, name: &str, value: &str) -> Result<(), String> {
+        self.state.borrow_mut().set_any(name, value)
+    }
+
+    fn properties() -> Vec<Property> {
+        let mut properties = main_properties();
+        properties.push(click_property());
+        properties
     }
 
     fn set_parent(&self, parent: Option<WeakElement>) {
@@ -136,30 +127,12 @@ impl View for List {
         }
     }
 
-    fn layout_content(&mut self, x: i32, y: i32, width: i32, height: i32, typeface: &Typeface, scale: f64) -> Rect<i32> {
-        self.state.borrow_mut().typeface = Some(typeface.clone());
+    fn measure(&mut self, width: i32, height: i32, typeface: &Typeface, scale: f64) -> (i32, i32) {
+        let typeface = self.state.borrow().get_typeface(typeface);
+        self.state.borrow_mut().resolved_typeface = Some(typeface);
         self.state.borrow_mut().scale = scale;
-        let (width, height) = {
-            let state = self.state.borrow_mut();
-            let ww;
-            let hh;
-            match &state.width {
-                Dimension::Min => ww = 0,
-                Dimension::Max => ww = width,
-                Dimension::Dip(dip) => ww = *dip as i32,
-                Dimension::Percent(p) => ww = (width as f32 * p / 100f32).round() as i32
-            }
-            match &state.height {
-                Dimension::Min => hh = 0,
-                Dimension::Max => hh = height,
-                Dimension::Dip(dip) => hh = *dip as i32,
-                Dimension::Percent(p) => hh = (height as f32 * p / 100f32).round() as i32
-            }
-            (ww, hh)
-        };
-        let rect = rect((x, y), (x + width, y + height));
-        self.set_rect(rect);
-        rect
+        let content = self.calculate_full_size(scale);
+        self.resolve_size(width, height, content, scale)
     }
 
     fn fits_in_rect(&self, width: i32, height: i32, _scale: f64) -> bool {
@@ -173,8 +146,10 @@ impl View for List {
         theme.push_clip();
         theme.clip_rect(rect);
         let state = self.get_state().unwrap();
-        theme.draw_list_back(rect, state);
-        //let color = theme.get_text_color(self.state.borrow().state, &self.state.borrow().foreground);
+        if !theme.draw_styled_back(rect, state, &self.state.borrow().background) {
+            theme.draw_list_back(rect, state);
+        }
+        let color = theme.get_text_color(state, &self.state.borrow().foreground);
         let mut y = rect.min.y;
         let mut index = 0usize;
         let selected = *self.selected.borrow();
@@ -182,7 +157,7 @@ impl View for List {
         for v in self.texts.borrow().iter() {
             if let Some(text) = v {
                 let text_height = text.height().ceil() as i32;
-                let mut text_color: u32 = 0xff000000;
+                let mut text_color = color;
                 if let Some(s) = selected {
                     if s == index {
                         let rect = super::super::types::rect((rect.min.x + 2, (y + scroll_y)), (rect.max.x - 2, (y + scroll_y) + text_height));
@@ -242,7 +217,18 @@ impl View for List {
     }
 
     fn get_content_size(&self) -> (i32, i32) {
-        (100, 200)
+        let mut width = 0;
+        let mut height = 0;
+        for text in self.texts.borrow().iter() {
+            match text {
+                Some(text) => {
+                    width = width.max(text.width().ceil() as i32);
+                    height += text.height().ceil() as i32;
+                }
+                None => height += DEFAULT_TEXT_SIZE as i32
+            }
+        }
+        (width, height)
     }
 
     fn is_focused(&self) -> bool {
@@ -270,19 +256,41 @@ impl View for List {
     }
 
     fn set_id(&mut self, id: &str) {
-        self.state.borrow_mut().id = id.to_owned();
+        self.state.borrow_mut().set_id(id);
     }
 
     fn get_id(&self) -> String {
         self.state.borrow().id.clone()
     }
 
-    fn on_event(&mut self, _event: EventType, _func: Box<dyn FnMut(&mut UI, &dyn View) -> bool>) {
-        todo!()
+    fn get_main(&self) -> Option<RefMut<'_, FieldsMain>> {
+        Some(self.state.borrow_mut())
     }
 
-    fn click(&self, _ui: &mut UI) -> bool {
-        todo!()
+    fn on_event(&mut self, event: EventType, func: Box<dyn FnMut(&mut UI, &dyn View) -> bool>) {
+        self.listeners.borrow_mut().insert(event, func);
+    }
+
+    fn click(&self, ui: &mut UI) -> bool {
+        let listener = self.listeners.borrow_mut().remove(&EventType::Click);
+        if let Some(mut click) = listener {
+            let result = click(ui, self as &dyn View);
+            self.listeners.borrow_mut().insert(EventType::Click, click);
+            return result;
+        }
+        false
+    }
+
+    fn on_mouse_button_up(&self, ui: &mut UI, position: Vector2<i32>, button: MouseButton) -> bool {
+        let hit = self.state.borrow().rect.hit((position.x, position.y));
+        if matches!(button, MouseButton::Left) && self.state.borrow().state.pressed {
+            self.state.borrow_mut().state.pressed = false;
+            if hit {
+                self.click(ui);
+            }
+            return true;
+        }
+        false
     }
 
     fn on_mouse_button_down(&self, _ui: &mut UI, position: Vector2<i32>, button: MouseButton) -> bool {
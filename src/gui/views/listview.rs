@@ -1,12 +1,14 @@
-use std::cell::RefCell;
+use std::cell::{RefCell, RefMut};
+use std::collections::HashMap;
 use speedy2d::dimen::Vector2;
 use speedy2d::window::MouseButton;
 use gui::events::EventType;
 use gui::themes::{Theme, Typeface, ViewState};
+use gui::properties::Property;
 use gui::traits::{Element, View, WeakElement};
 use gui::types::{Point, Rect, rect};
-use gui::ui::UI;
-use gui::views::{Borders, Dimension, FieldsMain};
+use gui::ui::{EventListener, UI};
+use gui::views::{Borders, Dimension, FieldsMain, click_property, main_properties};
 
 pub trait ListItem {
     fn get_view(&self) -> Element;
@@ -18,7 +20,8 @@ pub struct ListView {
     views: RefCell<Vec<Element>>,
     items_focusable: bool,
     scroll_y: i32,
-    selected: RefCell<Option<usize>>
+    selected: RefCell<Option<usize>>,
+    listeners: RefCell<HashMap<EventType, EventListener>>
 }
 
 impl ListView {
@@ -29,7 +32,8 @@ impl ListView {
             views: RefCell::new(vec![]),
             items_focusable: true,
             scroll_y: 0,
-            selected: RefCell::new(None)
+            selected: RefCell::new(None),
+            listeners: RefCell::new(HashMap::new())
         }
     }
 
@@ -39,7 +43,7 @@ impl ListView {
         let mut y = 0;
         let width = self.get_rect().width();
         let max_height = 20000;
-        let typeface = self.state.borrow().typeface.clone().unwrap();
+        let typeface = self.state.borrow().resolved_typeface.clone().unwrap();
         let scale = self.state.borrow().scale;
         for i in self.items.borrow().iter() {
             let view = i.get_view();
@@ -78,26 +82,14 @@ impl ListView {
 }
 
 impl View for ListView {
-    fn set_any(&mut self, name: &str, value: &str) {
-        match name {
-            "left" => { self.set_x(value.parse().unwrap()) }
-            "top" => { self.set_y(value.parse().unwrap()) }
-            "width" => { self.set_width(value.parse().unwrap()) }
-            "height" => { self.set_height(value.parse().unwrap()) }
-            "padding" => { self.state.borrow_mut().padding.set_all(value.parse().unwrap_or(0)) }
-            "padding_top" => { self.state.borrow_mut().padding.top = value.parse().unwrap_or(0) }
-            "padding_left" => { self.state.borrow_mut().padding.left = value.parse().unwrap_or(0) }
-            "padding_right" => { self.state.borrow_mut().padding.right = value.parse().unwrap_or(0) }
-            "padding_bottom" => { self.state.borrow_mut().padding.bottom = value.parse().unwrap_or(0) }
-            "margin" => { self.state.borrow_mut().margin.set_all(value.parse().unwrap_or(0)) }
-            "margin_left" => { self.state.borrow_mut().margin.left = value.parse().unwrap_or(0) }
-            "margin_right" => { self.state.borrow_mut().margin.right = value.parse().unwrap_or(0) }
-            "margin_top" => { self.state.borrow_mut().margin.top = value.parse().unwrap_or(0) }
-            "margin_bottom" => { self.state.borrow_mut().margin.bottom = value.parse().unwrap_or(0) }
-            "id" => { self.set_id(value) }
-            "break" => { self.state.borrow_mut().break_line = value.parse().unwrap_or(false) }
-            &_ => {}
-        }
+    fn set_any(&mut self, name: &str, value: &str) -> Result<(), String> {
+        self.state.borrow_mut().set_any(name, value)
+    }
+
+    fn properties() -> Vec<Property> {
+        let mut properties = main_properties();
+        properties.push(click_property());
+        properties
     }
 
     fn set_parent(&self, parent: Option<WeakElement>) {
@@ -116,30 +108,12 @@ impl View for ListView {
         }
     }
 
-    fn layout_content(&mut self, x: i32, y: i32, width: i32, height: i32, typeface: &Typeface, scale: f64) -> Rect<i32> {
-        self.state.borrow_mut().typeface = Some(typeface.clone());
+    fn measure(&mut self, width: i32, height: i32, typeface: &Typeface, scale: f64) -> (i32, i32) {
+        let typeface = self.state.borrow().get_typeface(typeface);
+        self.state.borrow_mut().resolved_typeface = Some(typeface);
         self.state.borrow_mut().scale = scale;
-        let (width, height) = {
-            let state = self.state.borrow_mut();
-            let ww;
-            let hh;
-            match &state.width {
-                Dimension::Min => ww = 0,
-                Dimension::Max => ww = width,
-                Dimension::Dip(dip) => ww = *dip as i32,
-                Dimension::Percent(p) => ww = (width as f32 * p / 100f32).round() as i32
-            }
-            match &state.height {
-                Dimension::Min => hh = 0,
-                Dimension::Max => hh = height,
-                Dimension::Dip(dip) => hh = *dip as i32,
-                Dimension::Percent(p) => hh = (height as f32 * p / 100f32).round() as i32
-            }
-            (ww, hh)
-        };
-        let rect = rect((x, y), (x + width, y + height));
-        self.set_rect(rect);
-        rect
+        let content = self.calculate_full_size(scale);
+        self.resolve_size(width, height, content, scale)
     }
 
     fn fits_in_rect(&self, width: i32, height: i32, _scale: f64) -> bool {
@@ -153,8 +127,11 @@ impl View for ListView {
         rect.move_by(origin);
         theme.push_clip();
         theme.clip_rect(rect);
-        theme.draw_list_back(rect, self.get_state().unwrap());
-        theme.draw_list_body(rect, self.get_state().unwrap());
+        let state = self.get_state().unwrap();
+        if !theme.draw_styled_back(rect, state, &self.state.borrow().background) {
+            theme.draw_list_back(rect, state);
+        }
+        theme.draw_list_body(rect, state);
         for v in self.views.borrow().iter() {
             let v = v.try_borrow().unwrap();
             v.paint(start, theme);
@@ -204,7 +181,14 @@ impl View for ListView {
     }
 
     fn get_content_size(&self) -> (i32, i32) {
-        (100, 200)
+        let mut width = 0;
+        let mut height = 0;
+        for v in self.views.borrow().iter() {
+            let rect = v.borrow().get_rect();
+            width = width.max(rect.width());
+            height += rect.height();
+        }
+        (width, height)
     }
 
     fn is_focused(&self) -> bool {
@@ -232,19 +216,41 @@ impl View for ListView {
     }
 
     fn set_id(&mut self, id: &str) {
-        self.state.borrow_mut().id = id.to_owned();
+        self.state.borrow_mut().set_id(id);
     }
 
     fn get_id(&self) -> String {
         self.state.borrow().id.clone()
     }
 
-    fn on_event(&mut self, _event: EventType, _func: Box<dyn FnMut(&mut UI, &dyn View) -> bool>) {
-        todo!()
+    fn get_main(&self) -> Option<RefMut<'_, FieldsMain>> {
+        Some(self.state.borrow_mut())
+    }
+
+    fn on_event(&mut self, event: EventType, func: Box<dyn FnMut(&mut UI, &dyn View) -> bool>) {
+        self.listeners.borrow_mut().insert(event, func);
+    }
+
+    fn click(&self, ui: &mut UI) -> bool {
+        let listener = self.listeners.borrow_mut().remove(&EventType::Click);
+        if let Some(mut click) = listener {
+            let result = click(ui, self as &dyn View);
+            self.listeners.borrow_mut().insert(EventType::Click, click);
+            return result;
+        }
+        false
     }
 
-    fn click(&self, _ui: &mut UI) -> bool {
-        todo!()
+    fn on_mouse_button_up(&self, ui: &mut UI, position: Vector2<i32>, button: MouseButton) -> bool {
+        let hit = self.state.borrow().rect.hit((position.x, position.y));
+        if matches!(button, MouseButton::Left) && self.state.borrow().state.pressed {
+            self.state.borrow_mut().state.pressed = false;
+            if hit {
+                self.click(ui);
+            }
+            return true;
+        }
+        false
     }
 
     fn on_mouse_button_down(&self, _ui: &mut UI, position: Vector2<i32>, button: MouseButton) -> bool {
@@ -5,23 +5,73 @@ pub mod checkbox;
 pub mod list;
 pub mod listview;
 
-use gui::themes::{Typeface, ViewState};
+use gui::themes::{FontStyle, Typeface, ViewState};
 use gui::traits::{View, WeakElement};
 use gui::types::Rect;
 use std::rc::Rc;
 use speedy2d::font::FormattedTextBlock;
 use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
 use std::str::FromStr;
 use gui::common::random_string;
 use gui::events::EventType;
+use gui::properties::{Property, PropertyType};
 use gui::ui::UI;
-use super::styles::selector::{MainSelector, FontSelector};
+use super::styles::selector::{Font, FontSelector, MainSelector};
 pub use self::label::Label;
 pub use self::button::Button;
 pub use self::edit::Edit;
 pub use self::checkbox::CheckBox;
 pub use self::list::List;
 
+/// Properties that all views have
+pub fn main_properties() -> Vec<Property> {
+    vec![
+        Property::new("left", PropertyType::Int, "0", "Position from the left side of parent"),
+        Property::new("top", PropertyType::Int, "0", "Position from the top side of parent"),
+        Property::new("width", PropertyType::Dimension, "min", "Width of the view"),
+        Property::new("height", PropertyType::Dimension, "min", "Height of the view"),
+        Property::new("padding", PropertyType::Int, "0", "Space inside of the view from all sides"),
+        Property::new("padding_top", PropertyType::Int, "0", "Space inside of the view from the top"),
+        Property::new("padding_left", PropertyType::Int, "0", "Space inside of the view from the left"),
+        Property::new("padding_right", PropertyType::Int, "0", "Space inside of the view from the right"),
+        Property::new("padding_bottom", PropertyType::Int, "0", "Space inside of the view from the bottom"),
+        Property::new("margin", PropertyType::Int, "0", "Space around the view from all sides"),
+        Property::new("margin_top", PropertyType::Int, "0", "Space around the view from the top"),
+        Property::new("margin_left", PropertyType::Int, "0", "Space around the view from the left"),
+        Property::new("margin_right", PropertyType::Int, "0", "Space around the view from the right"),
+        Property::new("margin_bottom", PropertyType::Int, "0", "Space around the view from the bottom"),
+        Property::new("id", PropertyType::String, "", "Identifier to find the view, random if not set"),
+        Property::new("class", PropertyType::String, "", "Classes for stylesheets, separated by spaces"),
+        Property::new("break", PropertyType::Bool, "false", "Starts a new line after this view in breaking frame"),
+        Property::new("row", PropertyType::Int, "0", "Row of the view in parent grid, starting from 0"),
+        Property::new("column", PropertyType::Int, "0", "Column of the view in parent grid, starting from 0"),
+        Property::new("row_span", PropertyType::Int, "1", "Number of rows that the view takes in parent grid"),
+        Property::new("column_span", PropertyType::Int, "1", "Number of columns that the view takes in parent grid"),
+        Property::new("weight", PropertyType::Float, "0", "Share of the space that is left from other views in parent frame"),
+        Property::new("gravity", PropertyType::Gravity, "start", "Place of the view across the direction of parent frame"),
+        Property::new("min_width", PropertyType::Int, "", "Width in dips, that the view never gets smaller than"),
+        Property::new("max_width", PropertyType::Int, "", "Width in dips, that the view never gets larger than"),
+        Property::new("min_height", PropertyType::Int, "", "Height in dips, that the view never gets smaller than"),
+        Property::new("max_height", PropertyType::Int, "", "Height in dips, that the view never gets larger than"),
+        Property::new("aspect_ratio", PropertyType::Ratio, "", "Ratio of width to height, like 16:9 or 1.5"),
+        Property::new("font", PropertyType::String, "", "Name of the font, the font of parent if not set"),
+        Property::new("font_style", PropertyType::FontStyle, "", "Style of the font, the style of parent if not set")
+    ]
+}
+
+/// Property, that binds the handler of click by its name
+pub fn click_property() -> Property {
+    Property::new("on_click", PropertyType::Handler, "", "Name of the handler, that is called on click")
+}
+
+/// Properties of views with text
+pub fn text_properties() -> Vec<Property> {
+    let mut properties = main_properties();
+    properties.push(Property::new("text", PropertyType::String, "", "Text of the view"));
+    properties
+}
+
 pub const BUTTON_MIN_WIDTH: i32 = 80;
 pub const BUTTON_MIN_HEIGHT: i32 = 24;
 
@@ -34,12 +84,35 @@ pub struct FieldsMain {
     pub margin: Borders,
     pub scale: f64,
     pub id: String,
+    /// The id is random, as it was not set from XML or code
+    pub generated_id: bool,
     pub state: ViewState,
     pub break_line: bool,
+    /// Place of the view in parent `Grid`
+    pub cell: GridCell,
+    /// Share of the free space along the direction of parent `Frame`, 0 means no share
+    pub weight: f32,
+    /// Place of the view across the direction of parent `Frame`
+    pub gravity: Gravity,
+    /// Limits of the size in dips, `None` if there is no limit
+    pub min_width: Option<u32>,
+    pub max_width: Option<u32>,
+    pub min_height: Option<u32>,
+    pub max_height: Option<u32>,
+    /// Ratio of width to height, that the view keeps
+    pub aspect_ratio: Option<AspectRatio>,
     pub background: MainSelector,
     pub foreground: MainSelector,
     pub parent: Option<WeakElement>,
-    pub typeface: Option<Typeface>
+    /// Classes of the view, that stylesheets can target
+    pub classes: Vec<String>,
+    pub font: FontSelector,
+    /// Font that is set for this view, the font of parent if not set
+    pub font_name: Option<String>,
+    /// Font style that is set for this view, the style of parent if not set
+    pub font_style: Option<FontStyle>,
+    /// Typeface that the view was laid out with
+    pub resolved_typeface: Option<Typeface>
 }
 
 impl FieldsMain {
@@ -54,13 +127,189 @@ impl FieldsMain {
             margin: Borders::default(),
             scale: 1.0,
             id: random_string(16),
+            generated_id: true,
             state: ViewState::default(),
             break_line: false,
+            cell: GridCell::default(),
+            weight: 0f32,
+            gravity: Gravity::default(),
+            min_width: None,
+            max_width: None,
+            min_height: None,
+            max_height: None,
+            aspect_ratio: None,
             background: MainSelector::new(),
             foreground: MainSelector::new(),
             parent: None,
-            typeface: None
+            classes: Vec::new(),
+            font: FontSelector::new(),
+            font_name: None,
+            font_style: None,
+            resolved_typeface: None
+        }
+    }
+
+    /// Gets the typeface to lay out this view with.
+    /// Parent typeface is changed by fonts of styles for current state, and then by own typeface.
+    pub fn get_typeface(&self, parent_typeface: &Typeface) -> Typeface {
+        let mut result = parent_typeface.clone();
+        for font in self.font.get_states(&self.state) {
+            match font {
+                Font::Name(name) => result.font_name = name.clone(),
+                Font::Style(style) => result.font_style = style.clone(),
+                Font::Color(_) => {}
+            }
+        }
+        if let Some(name) = &self.font_name {
+            result.font_name = name.clone();
+        }
+        if let Some(style) = &self.font_style {
+            result.font_style = style.clone();
+        }
+        result
+    }
+
+    /// Sets the font of this view, empty name means the font of parent
+    pub fn set_font(&mut self, font_name: &str) {
+        self.font_name = Some(font_name.to_owned()).filter(|n| !n.is_empty());
+    }
+
+    pub fn set_font_style(&mut self, style: &str) {
+        self.font_style = Some(FontStyle::from(style));
+    }
+
+    pub fn set_id(&mut self, id: &str) {
+        self.id = id.to_owned();
+        self.generated_id = false;
+    }
+
+    /// Sets classes from a list, separated by spaces
+    pub fn set_classes(&mut self, classes: &str) {
+        self.classes = classes.split_whitespace().map(|c| c.to_owned()).collect();
+    }
+
+    pub fn has_class(&self, class: &str) -> bool {
+        self.classes.iter().any(|c| c == class)
+    }
+
+    /// Keeps the size with padding in limits of min and max sizes
+    pub fn limit_size(&self, width: i32, height: i32, scale: f64) -> (i32, i32) {
+        let dips = |size: Option<u32>| size.map(|s| (s as f64 * scale).round() as i32);
+        let mut width = width;
+        let mut height = height;
+        if let Some(max) = dips(self.max_width) {
+            width = width.min(max);
+        }
+        if let Some(min) = dips(self.min_width) {
+            width = width.max(min);
+        }
+        if let Some(max) = dips(self.max_height) {
+            height = height.min(max);
+        }
+        if let Some(min) = dips(self.min_height) {
+            height = height.max(min);
+        }
+        (width, height)
+    }
+
+    /// Changes the size with padding to keep the aspect ratio, if it is set, and then limits it.
+    /// The side that wraps the content follows the other side, or the size shrinks to fit if both are set.
+    pub fn constrain_size(&self, width: i32, height: i32, scale: f64) -> (i32, i32) {
+        let (width, height) = match self.aspect_ratio {
+            None => (width, height),
+            Some(ratio) => {
+                let ratio = ratio.value();
+                let by_width = (width, (width as f32 / ratio).round() as i32);
+                let by_height = ((height as f32 * ratio).round() as i32, height);
+                match (&self.width, &self.height) {
+                    (Dimension::Min, Dimension::Min) => by_width,
+                    (Dimension::Min, _) => by_height,
+                    (_, Dimension::Min) => by_width,
+                    _ if by_width.1 <= height => by_width,
+                    _ => by_height
+                }
+            }
+        };
+        self.limit_size(width, height, scale)
+    }
+
+    /// Sets the main property by its name, as it is written in XML.
+    /// Unknown properties are skipped, views set their own ones before calling this.
+    pub fn set_any(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "left" => { self.rect.move_to((parse_value(value)?, self.rect.min.y)) }
+            "top" => { self.rect.move_to((self.rect.min.x, parse_value(value)?)) }
+            "width" => { self.width = parse_value(value)? }
+            "height" => { self.height = parse_value(value)? }
+            "padding" => { self.padding.set_all(parse_value(value)?) }
+            "padding_top" => { self.padding.top = parse_value(value)? }
+            "padding_left" => { self.padding.left = parse_value(value)? }
+            "padding_right" => { self.padding.right = parse_value(value)? }
+            "padding_bottom" => { self.padding.bottom = parse_value(value)? }
+            "margin" => { self.margin.set_all(parse_value(value)?) }
+            "margin_top" => { self.margin.top = parse_value(value)? }
+            "margin_left" => { self.margin.left = parse_value(value)? }
+            "margin_right" => { self.margin.right = parse_value(value)? }
+            "margin_bottom" => { self.margin.bottom = parse_value(value)? }
+            "id" => { self.set_id(value) }
+            "class" => { self.set_classes(value) }
+            "font" => { self.set_font(value) }
+            "font_style" => { self.set_font_style(value) }
+            "break" => { self.break_line = parse_value(value)? }
+            "row" => { self.cell.row = parse_value(value)? }
+            "column" => { self.cell.column = parse_value(value)? }
+            "row_span" => { self.cell.row_span = parse_value(value)? }
+            "column_span" => { self.cell.column_span = parse_value(value)? }
+            "weight" => { self.weight = parse_value(value)? }
+            "gravity" => { self.gravity = parse_value(value)? }
+            "min_width" => { self.min_width = Some(check_limits(name, parse_value(value)?, self.max_width)?) }
+            "max_width" => { self.max_width = Some(check_limits(name, parse_value(value)?, self.min_width)?) }
+            "min_height" => { self.min_height = Some(check_limits(name, parse_value(value)?, self.max_height)?) }
+            "max_height" => { self.max_height = Some(check_limits(name, parse_value(value)?, self.min_height)?) }
+            "aspect_ratio" => { self.aspect_ratio = Some(parse_value(value)?) }
+            &_ => {}
         }
+        Ok(())
+    }
+
+    /// Gets the value of main property by its name, as it would be written in XML.
+    /// Returns `None` for unknown properties, generated ids, fonts that are taken from parent,
+    /// and `padding` or `margin` when their sides differ.
+    pub fn get_any(&self, name: &str) -> Option<String> {
+        let value = match name {
+            "left" => self.rect.min.x.to_string(),
+            "top" => self.rect.min.y.to_string(),
+            "width" => self.width.to_string(),
+            "height" => self.height.to_string(),
+            "padding" => self.padding.get_all()?.to_string(),
+            "padding_top" => self.padding.top.to_string(),
+            "padding_left" => self.padding.left.to_string(),
+            "padding_right" => self.padding.right.to_string(),
+            "padding_bottom" => self.padding.bottom.to_string(),
+            "margin" => self.margin.get_all()?.to_string(),
+            "margin_top" => self.margin.top.to_string(),
+            "margin_left" => self.margin.left.to_string(),
+            "margin_right" => self.margin.right.to_string(),
+            "margin_bottom" => self.margin.bottom.to_string(),
+            "id" if !self.generated_id => self.id.clone(),
+            "class" => self.classes.join(" "),
+            "break" => self.break_line.to_string(),
+            "row" => self.cell.row.to_string(),
+            "column" => self.cell.column.to_string(),
+            "row_span" => self.cell.row_span.to_string(),
+            "column_span" => self.cell.column_span.to_string(),
+            "weight" => self.weight.to_string(),
+            "gravity" => self.gravity.to_string(),
+            "min_width" => self.min_width?.to_string(),
+            "max_width" => self.max_width?.to_string(),
+            "min_height" => self.min_height?.to_string(),
+            "max_height" => self.max_height?.to_string(),
+            "aspect_ratio" => self.aspect_ratio?.to_string(),
+            "font" => self.font_name.clone()?,
+            "font_style" => self.font_style.as_ref()?.to_string(),
+            &_ => return None
+        };
+        Some(value)
     }
 }
 
@@ -72,12 +321,74 @@ pub struct FieldsTexted {
     pub line_height: f32,
     pub single_line: bool,
     pub cached_text: Option<Rc<FormattedTextBlock>>,
-    pub font: FontSelector,
     pub listeners: HashMap<EventType, Box<dyn FnMut(&mut UI, &dyn View) -> bool>>
 }
 
+/// Checks that the minimum size is not more than the maximum one, `other` is the opposite limit of `name`
+fn check_limits(name: &str, value: u32, other: Option<u32>) -> Result<u32, String> {
+    let (min, max) = match name.starts_with("min_") {
+        true => (Some(value), other),
+        false => (other, Some(value))
+    };
+    match (min, max) {
+        (Some(min), Some(max)) if min > max => Err(format!("Wrong value {}: minimum {} is more than maximum {}", value, min, max)),
+        _ => Ok(value)
+    }
+}
+
+/// Ratio of width to height, written as `16:9` or `1.5`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AspectRatio {
+    pub width: f32,
+    pub height: f32
+}
+
+impl AspectRatio {
+    pub fn value(&self) -> f32 {
+        self.width / self.height
+    }
+}
+
+impl FromStr for AspectRatio {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (width, height) = s.split_once(':').unwrap_or((s, "1"));
+        let width = width.trim().parse::<f32>().map_err(|e| format!("{}, expected ratios like 16:9 or 1.5", e))?;
+        let height = height.trim().parse::<f32>().map_err(|e| format!("{}, expected ratios like 16:9 or 1.5", e))?;
+        if width <= 0f32 || height <= 0f32 {
+            return Err(String::from("parts of ratio must be positive"));
+        }
+        Ok(AspectRatio { width, height })
+    }
+}
+
+impl Display for AspectRatio {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.height == 1f32 {
+            true => write!(f, "{}", self.width),
+            false => write!(f, "{}:{}", self.width, self.height)
+        }
+    }
+}
+
+/// Row and column of the view in parent `Grid`, and how many of them the view takes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GridCell {
+    pub row: usize,
+    pub column: usize,
+    pub row_span: usize,
+    pub column_span: usize
+}
+
+impl Default for GridCell {
+    fn default() -> Self {
+        GridCell { row: 0, column: 0, row_span: 1, column_span: 1 }
+    }
+}
+
 /// Represents padding (inner spaces) or margin (outer spaces) of any element.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Borders {
     pub top: i32,
     pub left: i32,
@@ -102,6 +413,14 @@ impl Borders {
         self.bottom = padding;
     }
 
+    /// Gets the size of all sides if they are equal
+    pub fn get_all(&self) -> Option<i32> {
+        match self.top == self.left && self.top == self.right && self.top == self.bottom {
+            true => Some(self.top),
+            false => None
+        }
+    }
+
     pub fn scaled(&self, scale: f64) -> Self {
         Self {
             top: (self.top as f64 * scale).ceil() as i32,
@@ -131,30 +450,18 @@ pub enum Dimension {
 }
 
 impl FromStr for Dimension {
-    type Err = ();
+    type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let result = match s {
             "max" => Dimension::Max,
             "min" => Dimension::Min,
             &_ => {
-                if s.ends_with("%") {
-                    let float = match s[0..s.len()-1].parse::<f32>() {
-                        Ok(float) => float,
-                        Err(e) => {
-                            println!("Error parsing {}, {}", s, e);
-                            0f32
-                        }
-                    };
+                if let Some(percent) = s.strip_suffix('%') {
+                    let float = percent.parse::<f32>().map_err(|e| format!("{}, expected percents like 50%", e))?;
                     Dimension::Percent(float)
                 } else {
-                    let int = match s[0..s.len()].parse::<u32>() {
-                        Ok(int) => int,
-                        Err(e) => {
-                            println!("Error parsing {}, {}", s, e);
-                            0u32
-                        }
-                    };
+                    let int = s.parse::<u32>().map_err(|e| format!("{}, expected min, max, dips or percents", e))?;
                     Dimension::Dip(int)
                 }
             }
@@ -163,6 +470,17 @@ impl FromStr for Dimension {
     }
 }
 
+impl Display for Dimension {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Dimension::Min => write!(f, "min"),
+            Dimension::Max => write!(f, "max"),
+            Dimension::Dip(dip) => write!(f, "{}", dip),
+            Dimension::Percent(percent) => write!(f, "{}%", percent)
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum Direction {
     Horizontal,
@@ -176,13 +494,147 @@ impl Default for Direction {
 }
 
 impl FromStr for Direction {
-    type Err = ();
+    type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let result = match s {
-            "vertical" => Direction::Vertical,
-            &_ => Direction::Horizontal
-        };
-        Ok(result)
+        match s {
+            "vertical" => Ok(Direction::Vertical),
+            "horizontal" => Ok(Direction::Horizontal),
+            &_ => Err(String::from("expected horizontal or vertical"))
+        }
+    }
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Direction::Horizontal => write!(f, "horizontal"),
+            Direction::Vertical => write!(f, "vertical")
+        }
     }
-}
\ No newline at end of file
+}
+
+/// Placement of views along the direction of `Frame`, when they don't take all its space
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum Align {
+    #[default]
+    Start,
+    Center,
+    End,
+    /// The first and the last views are at the edges, and the space is shared between views
+    SpaceBetween
+}
+
+impl FromStr for Align {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "start" => Ok(Align::Start),
+            "center" => Ok(Align::Center),
+            "end" => Ok(Align::End),
+            "space-between" => Ok(Align::SpaceBetween),
+            &_ => Err(String::from("expected start, center, end or space-between"))
+        }
+    }
+}
+
+impl Display for Align {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Align::Start => write!(f, "start"),
+            Align::Center => write!(f, "center"),
+            Align::End => write!(f, "end"),
+            Align::SpaceBetween => write!(f, "space-between")
+        }
+    }
+}
+
+/// Placement of a view across the direction of its `Frame`
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum Gravity {
+    #[default]
+    Start,
+    Center,
+    End,
+    /// The view takes all the size of the frame, whatever its own dimension is
+    Stretch
+}
+
+impl FromStr for Gravity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "start" => Ok(Gravity::Start),
+            "center" => Ok(Gravity::Center),
+            "end" => Ok(Gravity::End),
+            "stretch" => Ok(Gravity::Stretch),
+            &_ => Err(String::from("expected start, center, end or stretch"))
+        }
+    }
+}
+
+impl Display for Gravity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Gravity::Start => write!(f, "start"),
+            Gravity::Center => write!(f, "center"),
+            Gravity::End => write!(f, "end"),
+            Gravity::Stretch => write!(f, "stretch")
+        }
+    }
+}
+
+/// Parses the value of attribute, the error tells what is wrong with it
+pub fn parse_value<T: FromStr>(value: &str) -> Result<T, String> where T::Err: Display {
+    value.parse().map_err(|e| format!("Wrong value {:?}: {}", value, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use gui::types::rect;
+    use gui::views::{Dimension, FieldsMain};
+
+    fn fields(width: Dimension, height: Dimension, attributes: &[(&str, &str)]) -> FieldsMain {
+        let mut fields = FieldsMain::with_rect(rect((0, 0), (0, 0)), width, height);
+        for (name, value) in attributes {
+            fields.set_any(name, value).unwrap();
+        }
+        fields
+    }
+
+    #[test]
+    fn size_is_limited() {
+        let limited = fields(Dimension::Min, Dimension::Min, &[("min_width", "50"), ("max_width", "100"), ("max_height", "40")]);
+        assert_eq!(limited.limit_size(20, 60, 1.0), (50, 40));
+        assert_eq!(limited.limit_size(120, 30, 1.0), (100, 30));
+        assert_eq!(limited.limit_size(250, 90, 2.0), (200, 80));
+        assert_eq!(limited.limit_size(80, 30, 2.0), (100, 30));
+    }
+
+    #[test]
+    fn aspect_ratio_follows_free_side() {
+        let by_width = fields(Dimension::Dip(160), Dimension::Min, &[("aspect_ratio", "16:9")]);
+        assert_eq!(by_width.constrain_size(160, 10, 1.0), (160, 90));
+        let by_height = fields(Dimension::Min, Dimension::Dip(90), &[("aspect_ratio", "16:9")]);
+        assert_eq!(by_height.constrain_size(10, 90, 1.0), (160, 90));
+        // Both sides are set, so the size shrinks to fit in them
+        let both = fields(Dimension::Max, Dimension::Max, &[("aspect_ratio", "2")]);
+        assert_eq!(both.constrain_size(300, 100, 1.0), (200, 100));
+        assert_eq!(both.constrain_size(100, 300, 1.0), (100, 50));
+        // Limits are applied after the ratio
+        let limited = fields(Dimension::Max, Dimension::Max, &[("aspect_ratio", "2"), ("max_width", "150")]);
+        assert_eq!(limited.constrain_size(300, 100, 1.0), (150, 100));
+    }
+
+    #[test]
+    fn minimum_more_than_maximum_is_wrong() {
+        let mut fields = fields(Dimension::Min, Dimension::Min, &[("max_width", "100"), ("min_height", "50")]);
+        assert!(fields.set_any("min_width", "120").is_err());
+        assert!(fields.set_any("max_height", "40").is_err());
+        assert!(fields.set_any("min_width", "100").is_ok());
+        assert_eq!(fields.min_width, Some(100));
+        assert_eq!(fields.max_height, None);
+    }
+}
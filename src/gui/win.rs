@@ -4,8 +4,9 @@ use speedy2d::dimen::Vector2;
 use speedy2d::Graphics2D;
 use speedy2d::window::{KeyScancode, ModifiersState, MouseButton, UserEventSender, VirtualKeyCode, WindowHandler, WindowHelper, WindowStartupInfo};
 
+use gui::render::SpeedyRenderer;
+use gui::themes::ThemeFactory;
 use gui::ui::UI;
-use gui::themes::*;
 
 pub struct Win<T> {
     ui: UI,
@@ -29,6 +30,16 @@ impl<T> Win<T> {
             t: PhantomData::default()
         }
     }
+
+    /// Creates the window, that paints with the theme made by given factory,
+    /// and uses its typeface for views by default.
+    /// The factory is registered in `UI` with the name, so it can be switched back to later.
+    pub fn with_theme<F: ThemeFactory + 'static>(mut ui: UI, sender: UserEventSender<WinEvent>, name: &str, factory: F) -> Self {
+        ui.set_typeface(factory.typeface());
+        ui.register_theme(name, factory);
+        ui.set_theme(name);
+        Win::new(ui, sender)
+    }
 }
 
 impl<T> WindowHandler<T> for Win<T> {
@@ -73,8 +84,9 @@ impl<T> WindowHandler<T> for Win<T> {
 
     fn on_draw(&mut self, helper: &mut WindowHelper<T>, graphics: &mut Graphics2D) {
         let scale = helper.get_scale_factor();
-        let mut theme = Classic::new(graphics, self.width as i32, self.height as i32, scale);
-        self.ui.paint(&mut theme);
+        let mut renderer = SpeedyRenderer::new(graphics, self.width as i32, self.height as i32);
+        let mut theme = self.ui.create_theme(&mut renderer, self.width as i32, self.height as i32, scale);
+        self.ui.paint(theme.as_mut());
     }
 
     fn on_mouse_move(&mut self, helper: &mut WindowHelper<T>, position: Vector2<f32>) {
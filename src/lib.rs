@@ -3,6 +3,8 @@ extern crate downcast_rs;
 extern crate quick_xml;
 extern crate speedy2d;
 extern crate rand;
+extern crate rusttype;
+extern crate image;
 
 pub mod gui;
 #[cfg(tests)]